@@ -4,7 +4,11 @@ use std::mem;
 
 use bevy_ecs::{
 	component::Component,
-	schedule::{IntoSystemConfigs, IntoSystemSetConfigs, Schedule, ScheduleBuildSettings, ScheduleLabel, Schedules},
+	event::{event_update_condition, event_update_system, Event, Events},
+	schedule::{
+		common_conditions::resource_exists, IntoSystemConfigs, IntoSystemSetConfigs, Schedule, ScheduleBuildSettings,
+		ScheduleLabel, Schedules,
+	},
 	system::Resource,
 	world::{FromWorld, World},
 };
@@ -232,6 +236,26 @@ impl App {
 		self
 	}
 
+	/// Setup the application to manage events of type `T`.
+	///
+	/// This is done by adding a [`Resource`] of type [`Events::<T>`], and
+	/// inserting an [`event_update_system`] into the given schedule, which
+	/// should run once per frame. Events are then kept alive for two runs of
+	/// that schedule, so that every system gets a chance to read them.
+	///
+	/// If the [`Events::<T>`] resource already exists, nothing happens.
+	pub fn add_event<T: Event>(&mut self, schedule: impl ScheduleLabel) -> &mut Self {
+		if !self.world.contains_resource::<Events<T>>() {
+			self.init_resource::<Events<T>>().add_systems(
+				schedule,
+				event_update_system::<T>
+					.run_if(resource_exists::<Events<T>>)
+					.run_if(event_update_condition::<T>),
+			);
+		}
+		self
+	}
+
 	/// Sets the function that will be called when the app is run.
 	///
 	/// The runner function `run_fn` is called only once by [`App::run`]. If the
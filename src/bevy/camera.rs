@@ -0,0 +1,269 @@
+#![allow(dead_code)]
+
+use bevy_ecs::{
+	bundle::Bundle,
+	change_detection::{DetectChanges, DetectChangesMut},
+	component::Component,
+	event::{Event, EventReader},
+	query::{AnyOf, With},
+	schedule::{InternedScheduleLabel, IntoSystemConfigs, ScheduleLabel},
+	system::{Query, Res, ResMut, Resource},
+	world::Ref,
+};
+use vek::Mat4;
+
+use super::{App, Plugin};
+use crate::{calc_projection_matrix, calc_view_matrix, Direction, Frustum, Position, Projection, ScreenSize};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// Marks an entity as a 3D camera.
+#[derive(Component, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Camera;
+
+/// The matrices of a camera, kept up to date by the [`Camera3dPlugin`].
+#[derive(Component, Copy, Clone, Debug, Default, PartialEq)]
+pub struct CameraMatrices {
+	pub view: Mat4<f32>,
+	pub projection: Mat4<f32>,
+	pub view_projection: Mat4<f32>,
+}
+
+/// The components of a 3D camera.
+#[derive(Bundle, Copy, Clone, Debug, PartialEq)]
+pub struct CameraBundle {
+	pub camera: Camera,
+	pub position: Position,
+	pub direction: Direction,
+	pub projection: Projection,
+	pub matrices: CameraMatrices,
+}
+
+/// The size of the viewport the cameras render to.
+#[derive(Resource, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ViewportSize(pub ScreenSize);
+
+/// Sent when the viewport is resized, usually from the window resize events.
+/// The [`ViewportSize`] is updated accordingly.
+#[derive(Event, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ViewportResized(pub ScreenSize);
+
+/// Recomputes the [`CameraMatrices`] of the cameras when their position,
+/// direction or projection change, and when the viewport is resized.
+///
+/// The projection of a camera is either a [`Projection`] or a plain
+/// [`Frustum`] component, the [`Projection`] wins when it has both.
+pub struct Camera3dPlugin {
+	/// The schedule the systems are added to, which should run once per frame.
+	pub schedule: InternedScheduleLabel,
+	/// The initial viewport size.
+	pub size: ScreenSize,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl CameraBundle {
+	pub fn new(position: Position, direction: Direction, projection: impl Into<Projection>) -> Self {
+		Self {
+			camera: Camera,
+			position,
+			direction,
+			projection: projection.into(),
+			// Computed by the plugin when the camera is spawned
+			matrices: CameraMatrices::default(),
+		}
+	}
+}
+
+impl CameraMatrices {
+	pub fn new(position: Position, direction: Direction, projection: Projection, size: ScreenSize) -> Self {
+		let view = calc_view_matrix(position, direction);
+		let projection = calc_projection_matrix(projection, size, None);
+		Self {
+			view,
+			projection,
+			view_projection: projection * view,
+		}
+	}
+}
+
+impl Camera3dPlugin {
+	pub fn new(schedule: impl ScheduleLabel, size: ScreenSize) -> Self {
+		Self {
+			schedule: schedule.intern(),
+			size,
+		}
+	}
+}
+
+impl Plugin for Camera3dPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_event::<ViewportResized>(self.schedule)
+			.insert_resource(ViewportSize(self.size))
+			.add_systems(self.schedule, (resize_viewport, update_camera_matrices).chain());
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+fn resize_viewport(mut events: EventReader<ViewportResized>, mut size: ResMut<ViewportSize>) {
+	// Only the last size matters, and the resource is only marked as changed
+	// when it actually changes
+	if let Some(&ViewportResized(new_size)) = events.read().last() {
+		size.set_if_neq(ViewportSize(new_size));
+	}
+}
+
+#[allow(clippy::type_complexity)]
+fn update_camera_matrices(
+	size: Res<ViewportSize>,
+	mut cameras: Query<
+		(
+			Ref<Position>,
+			Ref<Direction>,
+			AnyOf<(Ref<Projection>, Ref<Frustum>)>,
+			&mut CameraMatrices,
+		),
+		With<Camera>,
+	>,
+) {
+	for (position, direction, projection, mut matrices) in &mut cameras {
+		let (projection, projection_changed) = match projection {
+			(Some(projection), _) => (*projection, projection.is_changed()),
+			(None, Some(frustum)) => (Projection::from(*frustum), frustum.is_changed()),
+			(None, None) => unreachable!("AnyOf matches at least one component"),
+		};
+
+		// Also true when the components were just added
+		if size.is_changed() || position.is_changed() || direction.is_changed() || projection_changed {
+			*matrices = CameraMatrices::new(*position, *direction, projection, size.0);
+		}
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use bevy_ecs::entity::Entity;
+	use vek::Vec3;
+
+	use super::*;
+	use crate::{Degrees, Radians};
+
+	#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+	struct Update;
+
+	const FRUSTUM: Frustum = Frustum {
+		y_fov: Radians::new(1.),
+		z_near: 0.1,
+		z_far: 100.,
+	};
+
+	fn app() -> (App, Entity) {
+		let mut app = App::new();
+		app.add_plugin(Camera3dPlugin::new(Update, ScreenSize::new(800, 600)));
+		let camera = app
+			.world
+			.spawn(CameraBundle::new(Position(Vec3::new(1., 2., 3.)), Direction::default(), FRUSTUM))
+			.id();
+		app.world.run_schedule(Update);
+		(app, camera)
+	}
+
+	fn matrices(app: &App, camera: Entity) -> CameraMatrices {
+		*app.world.get::<CameraMatrices>(camera).unwrap()
+	}
+
+	#[test]
+	fn computed_on_spawn() {
+		let (app, camera) = app();
+		let matrices = matrices(&app, camera);
+
+		let position = Position(Vec3::new(1., 2., 3.));
+		assert_eq!(matrices.view, calc_view_matrix(position, Direction::default()));
+		assert_eq!(matrices.projection, calc_projection_matrix(FRUSTUM, ScreenSize::new(800, 600), None));
+		assert_relative_eq!(matrices.view_projection, matrices.projection * matrices.view);
+	}
+
+	#[test]
+	fn recomputed_on_change() {
+		let (mut app, camera) = app();
+
+		// Overwritten matrices are left alone while the inputs are unchanged
+		app.world.get_mut::<CameraMatrices>(camera).unwrap().view = Mat4::zero();
+		app.world.run_schedule(Update);
+		assert_eq!(matrices(&app, camera).view, Mat4::zero());
+
+		let direction = Direction {
+			yaw: Degrees::new(90.).into(),
+			pitch: Degrees::new(0.).into(),
+		};
+		*app.world.get_mut::<Direction>(camera).unwrap() = direction;
+		app.world.run_schedule(Update);
+		let position = Position(Vec3::new(1., 2., 3.));
+		assert_eq!(matrices(&app, camera).view, calc_view_matrix(position, direction));
+	}
+
+	#[test]
+	fn frustum_camera() {
+		let mut app = App::new();
+		app.add_plugin(Camera3dPlugin::new(Update, ScreenSize::new(800, 600)));
+		let camera = app
+			.world
+			.spawn((Camera, Position(Vec3::zero()), Direction::default(), FRUSTUM, CameraMatrices::default()))
+			.id();
+		app.world.run_schedule(Update);
+		assert_eq!(
+			matrices(&app, camera).projection,
+			calc_projection_matrix(FRUSTUM, ScreenSize::new(800, 600), None)
+		);
+
+		let frustum = Frustum {
+			z_far: 10.,
+			..FRUSTUM
+		};
+		*app.world.get_mut::<Frustum>(camera).unwrap() = frustum;
+		app.world.run_schedule(Update);
+		assert_eq!(
+			matrices(&app, camera).projection,
+			calc_projection_matrix(frustum, ScreenSize::new(800, 600), None)
+		);
+	}
+
+	#[test]
+	fn recomputed_on_resize() {
+		let (mut app, camera) = app();
+		app.world.get_mut::<CameraMatrices>(camera).unwrap().projection = Mat4::zero();
+
+		app.world.send_event(ViewportResized(ScreenSize::new(800, 600)));
+		app.world.run_schedule(Update);
+		assert_eq!(matrices(&app, camera).projection, Mat4::zero());
+
+		app.world.send_event(ViewportResized(ScreenSize::new(400, 400)));
+		app.world.send_event(ViewportResized(ScreenSize::new(1920, 1080)));
+		app.world.run_schedule(Update);
+		assert_eq!(app.world.resource::<ViewportSize>().0, ScreenSize::new(1920, 1080));
+		assert_eq!(
+			matrices(&app, camera).projection,
+			calc_projection_matrix(FRUSTUM, ScreenSize::new(1920, 1080), None)
+		);
+	}
+}
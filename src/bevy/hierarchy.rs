@@ -0,0 +1,319 @@
+#![allow(dead_code)]
+
+use std::ops::Deref;
+
+use bevy_ecs::{
+	bundle::Bundle,
+	change_detection::DetectChangesMut,
+	component::Component,
+	entity::Entity,
+	query::Without,
+	schedule::{InternedScheduleLabel, ScheduleLabel},
+	system::Query,
+	world::World,
+};
+
+use super::{App, Plugin};
+use crate::{GlobalTransform, Transform};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// The parent of an entity, whose [`Transform`] is relative to it.
+///
+/// Use [`set_parent`] and [`remove_parent`] to keep the [`Children`] of the
+/// parent in sync.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Parent(Entity);
+
+/// The entities whose [`Parent`] is this entity.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Children(Vec<Entity>);
+
+/// The components of an entity placed in the world.
+#[derive(Bundle, Copy, Clone, Debug, Default, PartialEq)]
+pub struct TransformBundle {
+	pub local: Transform,
+	/// Computed by the [`TransformPlugin`].
+	pub global: GlobalTransform,
+}
+
+/// Computes the [`GlobalTransform`] of the entities from their [`Transform`]
+/// and the ones of their ancestors.
+pub struct TransformPlugin {
+	/// The schedule the system is added to, which should run once per frame
+	/// after the transforms are updated.
+	pub schedule: InternedScheduleLabel,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl Parent {
+	pub fn get(&self) -> Entity {
+		self.0
+	}
+}
+
+impl Deref for Children {
+	type Target = [Entity];
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl TransformBundle {
+	pub fn new(local: Transform) -> Self {
+		Self {
+			local,
+			global: GlobalTransform::from(local),
+		}
+	}
+}
+
+impl TransformPlugin {
+	pub fn new(schedule: impl ScheduleLabel) -> Self {
+		Self {
+			schedule: schedule.intern(),
+		}
+	}
+}
+
+impl Plugin for TransformPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(self.schedule, propagate_transforms);
+	}
+}
+
+/// Makes `child` a child of `parent`, removing it from its previous parent.
+/// Does nothing if either entity doesn't exist.
+///
+/// # Panics
+///
+/// If `parent` is `child` or one of its descendants, which would create a
+/// cycle.
+pub fn set_parent(world: &mut World, child: Entity, parent: Entity) {
+	if world.get_entity(child).is_none() || world.get_entity(parent).is_none() {
+		return;
+	}
+
+	let mut ancestor = Some(parent);
+	while let Some(entity) = ancestor {
+		assert_ne!(entity, child, "an entity can't be its own ancestor");
+		ancestor = world.get::<Parent>(entity).map(Parent::get);
+	}
+	remove_parent(world, child);
+
+	world.entity_mut(child).insert(Parent(parent));
+	let mut parent = world.entity_mut(parent);
+	match parent.get_mut::<Children>() {
+		Some(mut children) => children.0.push(child),
+		None => {
+			parent.insert(Children(vec![child]));
+		}
+	}
+}
+
+/// Detaches `child` from its parent, if it has one. Its [`Transform`] is then
+/// relative to the world.
+pub fn remove_parent(world: &mut World, child: Entity) {
+	let Some(Parent(parent)) = world.get_entity_mut(child).and_then(|mut child| child.take::<Parent>()) else {
+		return;
+	};
+
+	let Some(mut parent) = world.get_entity_mut(parent) else {
+		return;
+	};
+	if let Some(mut children) = parent.get_mut::<Children>() {
+		children.0.retain(|&entity| entity != child);
+		if children.0.is_empty() {
+			parent.remove::<Children>();
+		}
+	}
+}
+
+/// Despawns an entity and all its descendants, detaching it from its parent.
+/// Descendants that were already despawned are skipped.
+pub fn despawn_recursive(world: &mut World, entity: Entity) {
+	remove_parent(world, entity);
+
+	let mut stack = vec![entity];
+	while let Some(entity) = stack.pop() {
+		let Some(mut entity) = world.get_entity_mut(entity) else {
+			continue;
+		};
+		if let Some(Children(children)) = entity.take::<Children>() {
+			stack.extend(children);
+		}
+		entity.despawn();
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+fn propagate_transforms(
+	roots: Query<Entity, Without<Parent>>,
+	mut transforms: Query<(&Transform, &mut GlobalTransform, Option<&Children>)>,
+) {
+	let mut stack = roots.iter().map(|root| (root, GlobalTransform::default())).collect::<Vec<_>>();
+
+	while let Some((entity, parent)) = stack.pop() {
+		let Ok((local, mut global, children)) = transforms.get_mut(entity) else {
+			continue;
+		};
+
+		// Only marks the unmoved entities as changed when they actually move
+		let new_global = parent.mul_transform(*local);
+		global.set_if_neq(new_global);
+
+		if let Some(children) = children {
+			stack.extend(children.iter().map(|&child| (child, new_global)));
+		}
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use vek::{Quaternion, Vec3};
+
+	use super::*;
+
+	#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+	struct Update;
+
+	fn spawn(app: &mut App, transform: Transform) -> Entity {
+		app.world.spawn(TransformBundle::new(transform)).id()
+	}
+
+	fn global(app: &App, entity: Entity) -> GlobalTransform {
+		*app.world.get::<GlobalTransform>(entity).unwrap()
+	}
+
+	#[test]
+	fn propagation() {
+		let mut app = App::new();
+		app.add_plugin(TransformPlugin::new(Update));
+
+		let root = spawn(
+			&mut app,
+			Transform::from_translation(Vec3::new(10., 0., 0.)).with_rotation(Quaternion::rotation_y(1.)),
+		);
+		let child = spawn(&mut app, Transform::from_translation(Vec3::unit_z()).with_scale(Vec3::broadcast(2.)));
+		let grandchild = spawn(&mut app, Transform::from_translation(Vec3::unit_x()));
+		set_parent(&mut app.world, child, root);
+		set_parent(&mut app.world, grandchild, child);
+		app.world.run_schedule(Update);
+
+		let root_transform = *app.world.get::<Transform>(root).unwrap();
+		let child_transform = *app.world.get::<Transform>(child).unwrap();
+		let expected = root_transform * child_transform * Transform::from_translation(Vec3::unit_x());
+		assert_relative_eq!(global(&app, grandchild).0, expected.to_matrix(), epsilon = 1e-5);
+
+		// Moving the root moves its descendants
+		app.world.get_mut::<Transform>(root).unwrap().translation = Vec3::zero();
+		app.world.run_schedule(Update);
+		let expected = root_transform.with_translation(Vec3::zero()) * child_transform;
+		assert_relative_eq!(global(&app, child).0, expected.to_matrix(), epsilon = 1e-5);
+	}
+
+	#[test]
+	fn reparenting() {
+		let mut app = App::new();
+		app.add_plugin(TransformPlugin::new(Update));
+
+		let first = spawn(&mut app, Transform::from_translation(Vec3::new(1., 0., 0.)));
+		let second = spawn(&mut app, Transform::from_translation(Vec3::new(0., 5., 0.)));
+		let child = spawn(&mut app, Transform::from_translation(Vec3::new(0., 0., 1.)));
+
+		set_parent(&mut app.world, child, first);
+		set_parent(&mut app.world, child, second);
+		assert!(app.world.get::<Children>(first).is_none());
+		assert_eq!(&**app.world.get::<Children>(second).unwrap(), &[child]);
+		assert_eq!(app.world.get::<Parent>(child).unwrap().get(), second);
+
+		app.world.run_schedule(Update);
+		assert_eq!(global(&app, child).translation(), Vec3::new(0., 5., 1.));
+
+		remove_parent(&mut app.world, child);
+		app.world.run_schedule(Update);
+		assert!(app.world.get::<Children>(second).is_none());
+		assert_eq!(global(&app, child).translation(), Vec3::new(0., 0., 1.));
+	}
+
+	#[test]
+	fn despawn_descendants() {
+		let mut app = App::new();
+		let root = spawn(&mut app, Transform::IDENTITY);
+		let child = spawn(&mut app, Transform::IDENTITY);
+		let grandchild = spawn(&mut app, Transform::IDENTITY);
+		let other = spawn(&mut app, Transform::IDENTITY);
+		set_parent(&mut app.world, child, root);
+		set_parent(&mut app.world, grandchild, child);
+		set_parent(&mut app.world, other, root);
+
+		despawn_recursive(&mut app.world, child);
+		assert!(app.world.get_entity(child).is_none());
+		assert!(app.world.get_entity(grandchild).is_none());
+		assert_eq!(&**app.world.get::<Children>(root).unwrap(), &[other]);
+	}
+
+	#[test]
+	fn missing_entities() {
+		let mut app = App::new();
+		let root = spawn(&mut app, Transform::IDENTITY);
+		let child = spawn(&mut app, Transform::IDENTITY);
+		let grandchild = spawn(&mut app, Transform::IDENTITY);
+		set_parent(&mut app.world, child, root);
+		set_parent(&mut app.world, grandchild, child);
+
+		// Despawned without updating the hierarchy, leaving a stale child
+		app.world.despawn(child);
+		set_parent(&mut app.world, child, root);
+		set_parent(&mut app.world, root, child);
+		remove_parent(&mut app.world, child);
+		assert!(app.world.get::<Parent>(root).is_none());
+
+		despawn_recursive(&mut app.world, root);
+		assert!(app.world.get_entity(root).is_none());
+		// The grandchild can't be reached through the despawned child
+		assert!(app.world.get_entity(grandchild).is_some());
+	}
+
+	#[test]
+	#[should_panic(expected = "an entity can't be its own ancestor")]
+	fn self_parent() {
+		let mut app = App::new();
+		let entity = spawn(&mut app, Transform::IDENTITY);
+		set_parent(&mut app.world, entity, entity);
+	}
+
+	#[test]
+	#[should_panic(expected = "an entity can't be its own ancestor")]
+	fn cycle() {
+		let mut app = App::new();
+		let root = spawn(&mut app, Transform::IDENTITY);
+		let child = spawn(&mut app, Transform::IDENTITY);
+		let grandchild = spawn(&mut app, Transform::IDENTITY);
+		set_parent(&mut app.world, child, root);
+		set_parent(&mut app.world, grandchild, child);
+		set_parent(&mut app.world, root, grandchild);
+	}
+}
@@ -1,6 +1,16 @@
 mod app;
 pub use app::*;
 
+#[cfg(feature = "camera_3d")]
+mod camera;
+#[cfg(feature = "camera_3d")]
+pub use camera::*;
+
+#[cfg(feature = "transform")]
+mod hierarchy;
+#[cfg(feature = "transform")]
+pub use hierarchy::*;
+
 mod plugin;
 
 // Re-export
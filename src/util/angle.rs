@@ -85,7 +85,7 @@ pub trait AngleRadiansType<T> {
 */
 
 impl<T, U: AngleType> Angle<T, U> {
-	pub fn new(value: T) -> Self {
+	pub const fn new(value: T) -> Self {
 		Self {
 			value,
 			_unit_type: PhantomData,
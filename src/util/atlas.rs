@@ -0,0 +1,446 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use anyhow::{ensure, Ok, Result};
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use vek::{Extent2, Vec2};
+use wgpu::{Device, Queue};
+
+use crate::{TextureAsset, TextureLoadOptions};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AtlasSettings {
+	/// The size of every page.
+	pub page_size: Extent2<u32>,
+	/// The empty pixels between two images.
+	pub padding: u32,
+	/// The number of times the edge pixels of each image are repeated around
+	/// it, so that filtering doesn't blend in the neighbouring images.
+	pub extrusion: u32,
+}
+
+/// Where an image is packed in an atlas, in pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AtlasRect {
+	pub page: usize,
+	/// The top-left corner of the image, without its extrusion.
+	pub position: Vec2<u32>,
+	pub size: Extent2<u32>,
+}
+
+/// Where an image is packed in an atlas, in texture coordinates.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AtlasUv {
+	pub page: usize,
+	/// The top-left corner.
+	pub min: Vec2<f32>,
+	/// The bottom-right corner.
+	pub max: Vec2<f32>,
+}
+
+/// Images packed into one or more pages, before they are uploaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AtlasLayout {
+	pub settings: AtlasSettings,
+	pub pages: Vec<RgbaImage>,
+	pub rects: HashMap<String, AtlasRect>,
+}
+
+/// Many small images packed into a few textures, with their coordinates
+/// looked up by label.
+pub struct TextureAtlas {
+	pub pages: Vec<TextureAsset>,
+	uvs: HashMap<String, AtlasUv>,
+}
+
+/// A bottom-left skyline bin-packer: the top edge of the packed rectangles is
+/// kept as a list of horizontal segments, and each rectangle is put where it
+/// ends up the lowest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Skyline {
+	size: Extent2<u32>,
+	/// The segments, from left to right, covering the whole width.
+	segments: Vec<Segment>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Segment {
+	x: u32,
+	y: u32,
+	width: u32,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl Default for AtlasSettings {
+	fn default() -> Self {
+		Self {
+			page_size: Extent2::new(1024, 1024),
+			padding: 2,
+			extrusion: 1,
+		}
+	}
+}
+
+impl AtlasRect {
+	/// The texture coordinates of the image on a page of `page_size`.
+	pub fn uv(&self, page_size: Extent2<u32>) -> AtlasUv {
+		let page_size = Vec2::from(page_size.as_::<f32>());
+		AtlasUv {
+			page: self.page,
+			min: self.position.as_::<f32>() / page_size,
+			max: (self.position + Vec2::from(self.size)).as_::<f32>() / page_size,
+		}
+	}
+}
+
+impl AtlasUv {
+	/// Maps texture coordinates of the original image to the atlas.
+	pub fn map(&self, uv: Vec2<f32>) -> Vec2<f32> {
+		self.min + (self.max - self.min) * uv
+	}
+}
+
+impl AtlasLayout {
+	/// Packs the images, tallest first, into as many pages as needed.
+	///
+	/// Fails if the labels aren't unique, or if an image doesn't fit in a page.
+	pub fn new(images: &[(&DynamicImage, &str)], settings: AtlasSettings) -> Result<Self> {
+		let sizes = images.iter().map(|(image, _)| Extent2::from(image.dimensions())).collect::<Vec<_>>();
+		let packed = pack_rects(&sizes, settings)?;
+
+		let page_count = packed.iter().map(|rect| rect.page + 1).max().unwrap_or(0);
+		let mut pages = vec![RgbaImage::new(settings.page_size.w, settings.page_size.h); page_count];
+		let mut rects = HashMap::new();
+
+		for (&(image, label), rect) in images.iter().zip(packed) {
+			ensure!(!rects.contains_key(label), "duplicate atlas label `{label}`");
+			draw_extruded(&mut pages[rect.page], &image.to_rgba8(), rect.position, settings.extrusion);
+			rects.insert(label.to_owned(), rect);
+		}
+
+		Ok(Self { settings, pages, rects })
+	}
+
+	pub fn uvs(&self) -> HashMap<String, AtlasUv> {
+		self.rects
+			.iter()
+			.map(|(label, rect)| (label.clone(), rect.uv(self.settings.page_size)))
+			.collect()
+	}
+}
+
+impl TextureAtlas {
+	pub fn from_images(
+		device: &Device,
+		queue: &Queue,
+		images: &[(&DynamicImage, &str)],
+		settings: AtlasSettings,
+		options: impl Into<TextureLoadOptions>,
+		label: Option<&str>,
+	) -> Result<Self> {
+		Self::from_layout(device, queue, AtlasLayout::new(images, settings)?, options, label)
+	}
+
+	pub fn from_layout(
+		device: &Device,
+		queue: &Queue,
+		layout: AtlasLayout,
+		options: impl Into<TextureLoadOptions>,
+		label: Option<&str>,
+	) -> Result<Self> {
+		let options = options.into();
+		let uvs = layout.uvs();
+		let pages = layout
+			.pages
+			.into_iter()
+			.map(|page| TextureAsset::from_image(device, queue, &DynamicImage::ImageRgba8(page), options, label))
+			.collect::<Result<_>>()?;
+
+		Ok(Self { pages, uvs })
+	}
+
+	pub fn uv(&self, label: &str) -> Option<AtlasUv> {
+		self.uvs.get(label).copied()
+	}
+}
+
+/// Packs rectangles into pages, returning where each one goes in input order.
+/// Every rectangle takes its size plus the extrusion on each side, and is at
+/// least `padding` pixels away from the others.
+pub fn pack_rects(sizes: &[Extent2<u32>], settings: AtlasSettings) -> Result<Vec<AtlasRect>> {
+	let AtlasSettings {
+		page_size,
+		padding,
+		extrusion,
+	} = settings;
+
+	// The padding is only needed between rectangles, so the page is made
+	// bigger by the padding taken on their right and bottom sides
+	let reserved = |size: Extent2<u32>| size + Extent2::broadcast(extrusion * 2 + padding);
+	let mut pages: Vec<Skyline> = Vec::new();
+	let mut rects = vec![None; sizes.len()];
+
+	// Packing the tallest rectangles first wastes less space
+	let mut order = (0..sizes.len()).collect::<Vec<_>>();
+	order.sort_by_key(|&i| (std::cmp::Reverse(sizes[i].h), std::cmp::Reverse(sizes[i].w)));
+
+	for i in order {
+		let size = reserved(sizes[i]);
+		ensure!(
+			size.w <= page_size.w + padding && size.h <= page_size.h + padding,
+			"a {}x{} image doesn't fit in a {}x{} atlas page",
+			sizes[i].w,
+			sizes[i].h,
+			page_size.w,
+			page_size.h,
+		);
+
+		let packed = pages.iter_mut().enumerate().find_map(|(page, skyline)| Some((page, skyline.insert(size)?)));
+		let (page, position) = match packed {
+			Some(packed) => packed,
+			None => {
+				let mut skyline = Skyline::new(page_size + Extent2::broadcast(padding));
+				let position = skyline.insert(size).expect("the rectangle fits in an empty page");
+				pages.push(skyline);
+				(pages.len() - 1, position)
+			}
+		};
+
+		rects[i] = Some(AtlasRect {
+			page,
+			position: position + extrusion,
+			size: sizes[i],
+		});
+	}
+
+	Ok(rects.into_iter().map(|rect| rect.expect("every rectangle is packed")).collect())
+}
+
+/// Copies `image` into `page` at `position`, repeating its edge pixels
+/// `extrusion` times around it. Empty images have no edge to repeat, so
+/// nothing is drawn.
+fn draw_extruded(page: &mut RgbaImage, image: &RgbaImage, position: Vec2<u32>, extrusion: u32) {
+	let (width, height) = image.dimensions();
+	if width == 0 || height == 0 {
+		return;
+	}
+	let extrusion = extrusion as i64;
+
+	for y in -extrusion..height as i64 + extrusion {
+		for x in -extrusion..width as i64 + extrusion {
+			let source = image.get_pixel(x.clamp(0, width as i64 - 1) as u32, y.clamp(0, height as i64 - 1) as u32);
+			let (page_x, page_y) = (position.x as i64 + x, position.y as i64 + y);
+			if page_x >= 0 && page_y >= 0 && page_x < page.width() as i64 && page_y < page.height() as i64 {
+				page.put_pixel(page_x as u32, page_y as u32, *source);
+			}
+		}
+	}
+}
+
+impl Skyline {
+	fn new(size: Extent2<u32>) -> Self {
+		Self {
+			size,
+			segments: vec![Segment {
+				x: 0,
+				y: 0,
+				width: size.w,
+			}],
+		}
+	}
+
+	/// Finds the lowest place for a rectangle, preferring the narrowest
+	/// segments on ties, and returns its top-left corner.
+	fn insert(&mut self, size: Extent2<u32>) -> Option<Vec2<u32>> {
+		if size.w == 0 || size.h == 0 {
+			return Some(Vec2::zero());
+		}
+
+		let (index, y) = (0..self.segments.len())
+			.filter_map(|index| Some((index, self.fit(index, size)?)))
+			.min_by_key(|&(index, y)| (y + size.h, self.segments[index].width))?;
+
+		let position = Vec2::new(self.segments[index].x, y);
+		self.add_segment(
+			index,
+			Segment {
+				x: position.x,
+				y: y + size.h,
+				width: size.w,
+			},
+		);
+		Some(position)
+	}
+
+	/// The height a rectangle starting at a segment would be placed at, if it
+	/// fits.
+	fn fit(&self, index: usize, size: Extent2<u32>) -> Option<u32> {
+		let x = self.segments[index].x;
+		if x + size.w > self.size.w {
+			return None;
+		}
+
+		let mut y = 0;
+		for segment in self.segments[index..].iter().take_while(|segment| segment.x < x + size.w) {
+			y = y.max(segment.y);
+		}
+
+		(y + size.h <= self.size.h).then_some(y)
+	}
+
+	/// Inserts a segment at `index`, shrinking or removing the segments it
+	/// covers, and merges the neighbours at the same height.
+	fn add_segment(&mut self, index: usize, segment: Segment) {
+		self.segments.insert(index, segment);
+		let end = segment.x + segment.width;
+
+		while let Some(next) = self.segments.get_mut(index + 1) {
+			if next.x >= end {
+				break;
+			}
+
+			let next_end = next.x + next.width;
+			if next_end <= end {
+				self.segments.remove(index + 1);
+			} else {
+				next.width = next_end - end;
+				next.x = end;
+				break;
+			}
+		}
+
+		let mut i = 0;
+		while i + 1 < self.segments.len() {
+			if self.segments[i].y == self.segments[i + 1].y {
+				self.segments[i].width += self.segments[i + 1].width;
+				self.segments.remove(i + 1);
+			} else {
+				i += 1;
+			}
+		}
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use image::Rgba;
+	use rstest::rstest;
+
+	use super::*;
+
+	fn settings(padding: u32, extrusion: u32) -> AtlasSettings {
+		AtlasSettings {
+			page_size: Extent2::new(64, 64),
+			padding,
+			extrusion,
+		}
+	}
+
+	fn overlaps(a: &AtlasRect, b: &AtlasRect, margin: u32) -> bool {
+		a.page == b.page
+			&& a.position.x < b.position.x + b.size.w + margin
+			&& b.position.x < a.position.x + a.size.w + margin
+			&& a.position.y < b.position.y + b.size.h + margin
+			&& b.position.y < a.position.y + a.size.h + margin
+	}
+
+	#[rstest]
+	#[case(0, 0)]
+	#[case(2, 0)]
+	#[case(2, 1)]
+	#[case(1, 3)]
+	fn packing_without_overlap(#[case] padding: u32, #[case] extrusion: u32) {
+		let settings = settings(padding, extrusion);
+		let sizes = (0..40).map(|i| Extent2::new(3 + i * 7 % 13, 2 + i * 5 % 11)).collect::<Vec<_>>();
+		let rects = pack_rects(&sizes, settings).unwrap();
+
+		for (i, rect) in rects.iter().enumerate() {
+			assert_eq!(rect.size, sizes[i]);
+			// The extrusion stays within the page
+			assert!(rect.position.x >= extrusion && rect.position.y >= extrusion);
+			assert!(rect.position.x + rect.size.w + extrusion <= 64);
+			assert!(rect.position.y + rect.size.h + extrusion <= 64);
+
+			for other in &rects[i + 1..] {
+				assert!(!overlaps(rect, other, extrusion * 2 + padding), "{rect:?} {other:?}");
+			}
+		}
+	}
+
+	#[test]
+	fn packing_pages() {
+		let sizes = vec![Extent2::new(32, 32); 9];
+		let rects = pack_rects(&sizes, settings(0, 0)).unwrap();
+		assert_eq!(rects.iter().filter(|rect| rect.page == 0).count(), 4);
+		assert_eq!(rects.iter().map(|rect| rect.page).max(), Some(2));
+
+		// Exactly the size of the page
+		assert!(pack_rects(&[Extent2::new(62, 62)], settings(4, 1)).is_ok());
+		assert!(pack_rects(&[Extent2::new(63, 10)], settings(4, 1)).is_err());
+	}
+
+	#[test]
+	fn uv_table() {
+		let rect = AtlasRect {
+			page: 1,
+			position: Vec2::new(16, 32),
+			size: Extent2::new(16, 8),
+		};
+		let uv = rect.uv(Extent2::new(64, 64));
+		assert_eq!(uv.page, 1);
+		assert_eq!(uv.min, Vec2::new(0.25, 0.5));
+		assert_eq!(uv.max, Vec2::new(0.5, 0.625));
+		assert_eq!(uv.map(Vec2::new(0.5, 1.)), Vec2::new(0.375, 0.625));
+	}
+
+	#[test]
+	fn layout_extrusion() {
+		let red = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255])));
+		let blue = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 3, Rgba([0, 0, 255, 255])));
+		let layout = AtlasLayout::new(&[(&red, "red"), (&blue, "blue")], settings(2, 1)).unwrap();
+
+		assert_eq!(layout.pages.len(), 1);
+		let page = &layout.pages[0];
+		for (label, color) in [("red", Rgba([255, 0, 0, 255])), ("blue", Rgba([0, 0, 255, 255]))] {
+			let rect = layout.rects[label];
+			let Vec2 { x, y } = rect.position;
+			assert_eq!(page.get_pixel(x, y), &color);
+			// The extruded corners and edges
+			assert_eq!(page.get_pixel(x - 1, y - 1), &color);
+			assert_eq!(page.get_pixel(x + rect.size.w, y + rect.size.h - 1), &color);
+			assert_eq!(layout.uvs()[label], rect.uv(Extent2::new(64, 64)));
+		}
+
+		assert!(AtlasLayout::new(&[(&red, "red"), (&blue, "red")], settings(2, 1)).is_err());
+	}
+
+	#[test]
+	fn layout_empty_images() {
+		let red = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255])));
+		let empty = DynamicImage::new_rgba8(0, 5);
+		let layout = AtlasLayout::new(&[(&red, "red"), (&empty, "empty")], settings(2, 3)).unwrap();
+
+		let uv = layout.uvs()["empty"];
+		assert_eq!(uv.min.x, uv.max.x);
+		let Vec2 { x, y } = layout.rects["red"].position;
+		assert_eq!(layout.pages[0].get_pixel(x, y), &Rgba([255, 0, 0, 255]));
+	}
+}
@@ -1,11 +1,11 @@
 #![allow(dead_code)]
 
-use std::f32::consts;
+use std::{f32::consts, mem};
 
 use derive_more::{Deref, Display, From, Into};
-use vek::{Extent2, Mat4, Vec3};
+use vek::{Extent2, FrustumPlanes, Mat4, Quaternion, Vec2, Vec3};
 
-use crate::{Angle, ScreenSize};
+use crate::{Angle, AngleRadiansType, Radians, ScreenSize};
 
 /*
 --------------------------------------------------------------------------------
@@ -27,11 +27,93 @@ pub struct Direction {
 
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Frustum {
-	pub y_fov: f32,
+	/// The vertical field of view.
+	pub y_fov: Radians,
 	pub z_near: f32,
 	pub z_far: f32,
 }
 
+/// Which dimension of the sensor of a physical camera is fitted to the screen,
+/// when their aspect ratios differ.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SensorFit {
+	/// The sensor width spans the screen width, like most camera specs assume.
+	#[default]
+	Horizontal,
+	Vertical,
+}
+
+/// The different ways of projecting the view space onto the screen.
+/// A plain [`Frustum`] converts into a [`Projection::Perspective`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+	/// A symmetric perspective projection.
+	Perspective(Frustum),
+	/// A symmetric perspective projection without a far plane.
+	InfinitePerspective { y_fov: Radians, z_near: f32 },
+	/// An orthographic projection showing `height` units vertically, the width
+	/// follows the aspect ratio of the screen.
+	Orthographic { height: f32, z_near: f32, z_far: f32 },
+	/// An orthographic projection with explicit view-space bounds.
+	OrthographicBounds(FrustumPlanes<f32>),
+	/// An asymmetric perspective projection, the bounds are given on the near
+	/// plane. The aspect ratio of the screen is ignored.
+	OffCenter(FrustumPlanes<f32>),
+}
+
+/// Which way the Z axis points relative to X (right) and Y (up).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Handedness {
+	/// +Z points forward, away from the viewer (wgpu, DirectX).
+	#[default]
+	Left,
+	/// +Z points backward, towards the viewer (OpenGL).
+	Right,
+}
+
+/// The range of the depth in normalized device coordinates.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DepthRange {
+	/// Depth goes from 0 to 1 (wgpu, Vulkan, DirectX).
+	#[default]
+	ZeroToOne,
+	/// Depth goes from -1 to 1 (OpenGL).
+	NegativeOneToOne,
+}
+
+/// The coordinate system and depth conventions expected by a renderer.
+///
+/// The free `calc_*` functions all use the default conventions: left-handed,
+/// zero-to-one depth and no reversed Z.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CameraConventions {
+	pub handedness: Handedness,
+	pub depth_range: DepthRange,
+	/// Maps the near plane to the far end of the depth range and vice-versa,
+	/// which spreads the precision of floating point depth buffers much more
+	/// evenly.
+	pub reversed_z: bool,
+}
+
+/// Cycles through the first `length` points of the Halton(2, 3) sequence, as
+/// sub-pixel jitter offsets in [-0.5, 0.5] for temporal anti-aliasing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct JitterSequence {
+	length: u32,
+	index: u32,
+}
+
+/// The current and previous view-projection matrices, for computing motion
+/// vectors and reprojecting the previous frame.
+///
+/// The matrices pushed should be unjittered, so that a static scene seen from
+/// a static camera has no motion.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ViewProjectionHistory {
+	current: Mat4<f32>,
+	previous: Mat4<f32>,
+}
+
 /*
 --------------------------------------------------------------------------------
 ||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
@@ -40,6 +122,9 @@ pub struct Frustum {
 
 pub const SAFE_FRAC_PI_2: f32 = consts::FRAC_PI_2 - 0.0001;
 
+/// The sensor size of a full-frame (35mm) camera, in millimeters.
+pub const FULL_FRAME_SENSOR: Extent2<f32> = Extent2::new(36., 24.);
+
 pub fn calc_forward_vector(Direction { yaw, pitch }: Direction) -> Vec3<f32> {
 	let (sin_yaw, cos_yaw) = yaw.sin_cos();
 	let (sin_pitch, cos_pitch) = pitch.sin_cos();
@@ -67,8 +152,412 @@ pub fn calc_view_matrix(Position(position): Position, direction: Direction) -> M
 	Mat4::look_at_lh(position, position + calc_forward_vector(direction), Vec3::unit_y())
 }
 
-pub fn calc_projection_matrix(Frustum { y_fov, z_near, z_far }: Frustum, Extent2 { w, h }: ScreenSize) -> Mat4<f32> {
-	Mat4::perspective_fov_lh_zo(y_fov, w as f32, h as f32, z_near, z_far)
+/// The projection matrix, optionally shifted by a sub-pixel `jitter` for
+/// temporal anti-aliasing, usually from a [`JitterSequence`].
+///
+/// The jitter is in pixels, with Y pointing down like screen coordinates.
+pub fn calc_projection_matrix(
+	projection: impl Into<Projection>,
+	Extent2 { w, h }: ScreenSize,
+	jitter: Option<Vec2<f32>>,
+) -> Mat4<f32> {
+	let (w, h) = (w as f32, h as f32);
+
+	let matrix = match projection.into() {
+		Projection::Perspective(Frustum { y_fov, z_near, z_far }) => {
+			Mat4::perspective_fov_lh_zo(y_fov.radians(), w, h, z_near, z_far)
+		}
+		Projection::InfinitePerspective { y_fov, z_near } => calc_infinite_perspective_matrix(y_fov, w / h, z_near),
+		Projection::Orthographic { height, z_near, z_far } => Mat4::orthographic_lh_zo(FrustumPlanes {
+			left: -height * w / h / 2.,
+			right: height * w / h / 2.,
+			bottom: -height / 2.,
+			top: height / 2.,
+			near: z_near,
+			far: z_far,
+		}),
+		Projection::OrthographicBounds(planes) => Mat4::orthographic_lh_zo(planes),
+		Projection::OffCenter(planes) => calc_off_center_perspective_matrix(planes),
+	};
+
+	match jitter {
+		// A translation in NDC, which works for both perspective and orthographic
+		// projections since it is applied before the perspective divide
+		Some(jitter) => Mat4::<f32>::translation_3d(Vec3::new(jitter.x * 2. / w, -jitter.y * 2. / h, 0.)) * matrix,
+		None => matrix,
+	}
+}
+
+/// Left-handed, zero-to-one depth. `vek`'s own `frustum_lh_zo` offsets the
+/// center in the wrong direction for left-handed spaces.
+fn calc_off_center_perspective_matrix(FrustumPlanes { left, right, bottom, top, near, far }: FrustumPlanes<f32>) -> Mat4<f32> {
+	let mut m = Mat4::zero();
+	m[(0, 0)] = 2. * near / (right - left);
+	m[(0, 2)] = -(right + left) / (right - left);
+	m[(1, 1)] = 2. * near / (top - bottom);
+	m[(1, 2)] = -(top + bottom) / (top - bottom);
+	m[(2, 2)] = far / (far - near);
+	m[(2, 3)] = -far * near / (far - near);
+	m[(3, 2)] = 1.;
+	m
+}
+
+/// Left-handed, zero-to-one depth. `vek`'s own infinite perspective only
+/// exists for a negative-one-to-one depth range.
+fn calc_infinite_perspective_matrix(y_fov: Radians, aspect_ratio: f32, z_near: f32) -> Mat4<f32> {
+	let focal = 1. / (y_fov / 2.).tan();
+
+	let mut m = Mat4::zero();
+	m[(0, 0)] = focal / aspect_ratio;
+	m[(1, 1)] = focal;
+	m[(2, 2)] = 1.;
+	m[(2, 3)] = -z_near;
+	m[(3, 2)] = 1.;
+	m
+}
+
+/// The horizontal field of view matching a vertical one on a screen.
+pub fn calc_x_fov(y_fov: impl Into<Radians>, Extent2 { w, h }: ScreenSize) -> Radians {
+	let y_fov = y_fov.into();
+	Radians::new(((y_fov / 2.).tan() * w as f32 / h as f32).atan() * 2.)
+}
+
+/// The vertical field of view matching a horizontal one on a screen.
+pub fn calc_y_fov(x_fov: impl Into<Radians>, Extent2 { w, h }: ScreenSize) -> Radians {
+	let x_fov = x_fov.into();
+	Radians::new(((x_fov / 2.).tan() * h as f32 / w as f32).atan() * 2.)
+}
+
+impl CameraConventions {
+	/// The conventions of wgpu, which are also the defaults.
+	pub const WGPU: Self = Self {
+		handedness: Handedness::Left,
+		depth_range: DepthRange::ZeroToOne,
+		reversed_z: false,
+	};
+
+	/// The conventions of a classic OpenGL pipeline.
+	pub const OPENGL: Self = Self {
+		handedness: Handedness::Right,
+		depth_range: DepthRange::NegativeOneToOne,
+		reversed_z: false,
+	};
+
+	pub fn forward_vector(self, direction: Direction) -> Vec3<f32> {
+		self.to_handedness(calc_forward_vector(direction))
+	}
+
+	pub fn forward_horizontal_vector(self, direction: Direction) -> Vec3<f32> {
+		self.to_handedness(calc_forward_horizontal_vector(direction))
+	}
+
+	pub fn right_vector(self, direction: Direction) -> Vec3<f32> {
+		self.to_handedness(calc_right_vector(direction))
+	}
+
+	pub fn up_vector(self, direction: Direction) -> Vec3<f32> {
+		self.to_handedness(calc_up_vector(direction))
+	}
+
+	pub fn view_matrix(self, Position(position): Position, direction: Direction) -> Mat4<f32> {
+		let target = position + self.forward_vector(direction);
+		match self.handedness {
+			Handedness::Left => Mat4::look_at_lh(position, target, Vec3::unit_y()),
+			Handedness::Right => Mat4::look_at_rh(position, target, Vec3::unit_y()),
+		}
+	}
+
+	pub fn projection_matrix(
+		self,
+		projection: impl Into<Projection>,
+		size: ScreenSize,
+		jitter: Option<Vec2<f32>>,
+	) -> Mat4<f32> {
+		self.convert_projection_matrix(calc_projection_matrix(projection, size, jitter))
+	}
+
+	/// Converts a projection matrix built with the default conventions (like the
+	/// ones from [`calc_projection_matrix`]) to these conventions.
+	pub fn convert_projection_matrix(self, mut matrix: Mat4<f32>) -> Mat4<f32> {
+		// The right-handed view space is the left-handed one with Z mirrored
+		if self.handedness == Handedness::Right {
+			matrix *= Mat4::scaling_3d(Vec3::new(1., 1., -1.));
+		}
+
+		// Operate on the depth row, the W row being the perspective divide
+		for col in 0..4 {
+			let (mut z, w) = (matrix[(2, col)], matrix[(3, col)]);
+
+			if self.reversed_z {
+				z = w - z;
+			}
+
+			if self.depth_range == DepthRange::NegativeOneToOne {
+				z = z * 2. - w;
+			}
+
+			matrix[(2, col)] = z;
+		}
+
+		matrix
+	}
+
+	/// The vectors of the `calc_*_vector` functions are left-handed, so a
+	/// right-handed world only needs its Z axis mirrored.
+	fn to_handedness(self, vector: Vec3<f32>) -> Vec3<f32> {
+		match self.handedness {
+			Handedness::Left => vector,
+			Handedness::Right => Vec3::new(vector.x, vector.y, -vector.z),
+		}
+	}
+}
+
+impl Direction {
+	/// The direction pointing along `forward`, the inverse of
+	/// [`calc_forward_vector`].
+	///
+	/// Straight up or down vectors have no defined yaw, so it is set to zero
+	/// there. The pitch is kept within [`SAFE_FRAC_PI_2`] so that the result
+	/// always makes a valid view matrix. A zero vector gives the default
+	/// direction.
+	pub fn from_forward(forward: Vec3<f32>) -> Self {
+		Self::from_forward_or_yaw(forward, Radians::new(0.))
+	}
+
+	/// The direction from `from` towards `to`, see [`from_forward`](Self::from_forward).
+	pub fn look_at(Position(from): Position, to: Vec3<f32>) -> Self {
+		Self::from_forward(to - from)
+	}
+
+	/// Like [`from_forward`](Self::from_forward), but using `fallback_yaw` when
+	/// the vector is vertical.
+	pub fn from_forward_or_yaw(forward: Vec3<f32>, fallback_yaw: Angle) -> Self {
+		let length = forward.magnitude();
+		if length <= f32::EPSILON {
+			return Self::default();
+		}
+
+		let forward = forward / length;
+		let pitch = forward.y.clamp(-1., 1.).asin().clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+		let yaw = if forward.x.hypot(forward.z) <= f32::EPSILON {
+			fallback_yaw
+		} else {
+			Radians::new(forward.x.atan2(forward.z))
+		};
+
+		Self {
+			yaw,
+			pitch: Radians::new(pitch),
+		}
+	}
+
+	/// The rotation turning the +Z axis into the forward vector and the +Y axis
+	/// into the up vector.
+	pub fn to_quaternion(self) -> Quaternion<f32> {
+		Quaternion::rotation_y(self.yaw.radians()) * Quaternion::rotation_x(-self.pitch.radians())
+	}
+
+	/// The direction a rotation points the +Z axis to. Any roll is discarded,
+	/// but the yaw of vertical directions is recovered from the rotation's up
+	/// vector.
+	pub fn from_quaternion(rotation: Quaternion<f32>) -> Self {
+		let forward = rotation * Vec3::unit_z();
+
+		// Looking straight up, the up vector points backwards, and the other way
+		// around when looking down
+		let up = rotation * Vec3::unit_y() * -forward.y.signum();
+		Self::from_forward_or_yaw(forward, Radians::new(up.x.atan2(up.z)))
+	}
+}
+
+impl From<Direction> for Quaternion<f32> {
+	fn from(direction: Direction) -> Self {
+		direction.to_quaternion()
+	}
+}
+
+impl From<Quaternion<f32>> for Direction {
+	fn from(rotation: Quaternion<f32>) -> Self {
+		Self::from_quaternion(rotation)
+	}
+}
+
+impl Frustum {
+	pub fn new(y_fov: impl Into<Radians>, z_near: f32, z_far: f32) -> Self {
+		Self {
+			y_fov: y_fov.into(),
+			z_near,
+			z_far,
+		}
+	}
+
+	/// A frustum with a given horizontal field of view on a screen.
+	pub fn from_x_fov(x_fov: impl Into<Radians>, size: ScreenSize, z_near: f32, z_far: f32) -> Self {
+		Self::new(calc_y_fov(x_fov, size), z_near, z_far)
+	}
+
+	/// The frustum of a physical camera, from the focal length of its lens and
+	/// the size of its sensor (in the same unit, usually millimeters).
+	pub fn from_focal_length(
+		focal_length: f32,
+		sensor_size: Extent2<f32>,
+		fit: SensorFit,
+		size: ScreenSize,
+		z_near: f32,
+		z_far: f32,
+	) -> Self {
+		match fit {
+			SensorFit::Horizontal => {
+				let x_fov = Radians::new((sensor_size.w / 2. / focal_length).atan() * 2.);
+				Self::from_x_fov(x_fov, size, z_near, z_far)
+			}
+			SensorFit::Vertical => {
+				let y_fov = Radians::new((sensor_size.h / 2. / focal_length).atan() * 2.);
+				Self::new(y_fov, z_near, z_far)
+			}
+		}
+	}
+
+	/// The horizontal field of view on a screen.
+	pub fn x_fov(&self, size: ScreenSize) -> Radians {
+		calc_x_fov(self.y_fov, size)
+	}
+
+	/// The focal length giving this field of view with a sensor of the given
+	/// height, the inverse of [`from_focal_length`](Self::from_focal_length)
+	/// with [`SensorFit::Vertical`].
+	pub fn focal_length(&self, sensor_height: f32) -> f32 {
+		sensor_height / 2. / (self.y_fov / 2.).tan()
+	}
+}
+
+impl Projection {
+	/// The distance to the near plane.
+	pub fn z_near(&self) -> f32 {
+		match *self {
+			Projection::Perspective(Frustum { z_near, .. })
+			| Projection::InfinitePerspective { z_near, .. }
+			| Projection::Orthographic { z_near, .. } => z_near,
+			Projection::OrthographicBounds(planes) | Projection::OffCenter(planes) => planes.near,
+		}
+	}
+
+	/// The distance to the far plane, infinite for
+	/// [`Projection::InfinitePerspective`].
+	pub fn z_far(&self) -> f32 {
+		match *self {
+			Projection::Perspective(Frustum { z_far, .. }) | Projection::Orthographic { z_far, .. } => z_far,
+			Projection::InfinitePerspective { .. } => f32::INFINITY,
+			Projection::OrthographicBounds(planes) | Projection::OffCenter(planes) => planes.far,
+		}
+	}
+}
+
+impl From<Frustum> for Projection {
+	fn from(frustum: Frustum) -> Self {
+		Projection::Perspective(frustum)
+	}
+}
+
+/// The `index`-th element of the Halton sequence in the given `base`, in
+/// [0, 1). The element 0 is always 0.
+pub fn halton(mut index: u32, base: u32) -> f32 {
+	let (mut result, mut fraction) = (0., 1.);
+	while index > 0 {
+		fraction /= base as f32;
+		result += fraction * (index % base) as f32;
+		index /= base;
+	}
+	result
+}
+
+impl JitterSequence {
+	/// # Panics
+	///
+	/// If `length` is 0.
+	pub fn new(length: u32) -> Self {
+		assert!(length > 0, "the jitter sequence must not be empty");
+		Self { length, index: 0 }
+	}
+
+	pub fn length(&self) -> u32 {
+		self.length
+	}
+
+	/// The jitter offset of the current frame.
+	pub fn current(&self) -> Vec2<f32> {
+		// Skips the first element, which is 0 in every base
+		let index = self.index + 1;
+		Vec2::new(halton(index, 2), halton(index, 3)) - 0.5
+	}
+
+	/// Moves on to the next offset, going back to the first one after `length`
+	/// offsets.
+	pub fn advance(&mut self) {
+		self.index = (self.index + 1) % self.length;
+	}
+
+	pub fn reset(&mut self) {
+		self.index = 0;
+	}
+}
+
+impl Default for JitterSequence {
+	/// The 8 offsets commonly used for TAA.
+	fn default() -> Self {
+		Self::new(8)
+	}
+}
+
+impl Iterator for JitterSequence {
+	type Item = Vec2<f32>;
+
+	/// Never returns [`None`], the sequence loops.
+	fn next(&mut self) -> Option<Self::Item> {
+		let current = self.current();
+		self.advance();
+		Some(current)
+	}
+}
+
+impl ViewProjectionHistory {
+	/// Starts with the same matrix for both frames, so that there is no motion
+	/// on the first frame.
+	pub fn new(view_projection: Mat4<f32>) -> Self {
+		Self {
+			current: view_projection,
+			previous: view_projection,
+		}
+	}
+
+	/// Starts a new frame, the current matrix becoming the previous one.
+	pub fn push(&mut self, view_projection: Mat4<f32>) {
+		self.previous = mem::replace(&mut self.current, view_projection);
+	}
+
+	pub fn current(&self) -> Mat4<f32> {
+		self.current
+	}
+
+	pub fn previous(&self) -> Mat4<f32> {
+		self.previous
+	}
+
+	/// Maps the clip space of the current frame to the clip space of the
+	/// previous one.
+	pub fn reprojection_matrix(&self) -> Mat4<f32> {
+		self.previous * self.current.inverted()
+	}
+
+	/// The motion of a world-space point since the previous frame, in normalized
+	/// device coordinates.
+	pub fn motion_vector(&self, point: Vec3<f32>) -> Vec2<f32> {
+		let project = |matrix: Mat4<f32>| {
+			let clip = matrix * point.with_w(1.);
+			clip.xy() / clip.w
+		};
+		project(self.current) - project(self.previous)
+	}
 }
 
 /*
@@ -81,12 +570,13 @@ pub fn calc_projection_matrix(Frustum { y_fov, z_near, z_far }: Frustum, Extent2
 mod bevy {
 	use bevy_ecs::component::{Component, TableStorage};
 
-	use super::{Direction, Frustum, Position};
+	use super::{Direction, Frustum, Position, Projection};
 
 	// #[rustfmt::skip] impl Component for ScreenSize {type Storage = TableStorage;}
 	#[rustfmt::skip] impl Component for Position   {type Storage = TableStorage;}
 	#[rustfmt::skip] impl Component for Direction  {type Storage = TableStorage;}
 	#[rustfmt::skip] impl Component for Frustum    {type Storage = TableStorage;}
+	#[rustfmt::skip] impl Component for Projection {type Storage = TableStorage;}
 }
 
 #[cfg(feature = "bevy")]
@@ -107,7 +597,7 @@ mod tests {
 	use vek::{num_traits::Float, Vec3};
 
 	use super::*;
-	use crate::util::{Angle, Degrees};
+	use crate::util::{Angle, AngleDegreesType, Degrees};
 
 	#[rstest]
 	#[case(Degrees::new(0.).into(),   Vec3::new(0., 0., 1.))]
@@ -166,4 +656,327 @@ mod tests {
 	fn camera_up(#[case] yaw: Angle, #[case] pitch: Angle, #[case] expected: Vec3<f32>) {
 		assert_relative_eq!(calc_up_vector(Direction { yaw, pitch }), expected);
 	}
+
+	fn project(matrix: Mat4<f32>, point: Vec3<f32>) -> Vec3<f32> {
+		let clip = matrix * point.with_w(1.);
+		clip.xyz() / clip.w
+	}
+
+	#[test]
+	fn projection_perspective_matches_frustum() {
+		let frustum = Frustum {
+			y_fov: Radians::new(1.2),
+			z_near: 0.1,
+			z_far: 100.,
+		};
+		let size = ScreenSize::new(1920, 1080);
+		assert_eq!(
+			calc_projection_matrix(frustum, size, None),
+			calc_projection_matrix(Projection::Perspective(frustum), size, None)
+		);
+	}
+
+	#[rstest]
+	#[case(Vec3::new(-8., -4.5, 1.),  Vec3::new(-1., -1., 0.))]
+	#[case(Vec3::new(8., 4.5, 11.),   Vec3::new(1., 1., 1.))]
+	#[case(Vec3::new(4., 0., 6.),     Vec3::new(0.5, 0., 0.5))]
+	fn projection_orthographic(#[case] point: Vec3<f32>, #[case] expected: Vec3<f32>) {
+		let size = ScreenSize::new(1600, 900);
+
+		let by_height = calc_projection_matrix(
+			Projection::Orthographic {
+				height: 9.,
+				z_near: 1.,
+				z_far: 11.,
+			},
+			size,
+			None,
+		);
+		assert_relative_eq!(project(by_height, point), expected, epsilon = 1e-5);
+
+		let by_bounds = calc_projection_matrix(
+			Projection::OrthographicBounds(FrustumPlanes {
+				left: -8.,
+				right: 8.,
+				bottom: -4.5,
+				top: 4.5,
+				near: 1.,
+				far: 11.,
+			}),
+			size,
+			None,
+		);
+		assert_relative_eq!(by_bounds, by_height, epsilon = 1e-5);
+	}
+
+	#[test]
+	fn projection_infinite_perspective() {
+		let matrix = calc_projection_matrix(
+			Projection::InfinitePerspective {
+				y_fov: Radians::new(consts::FRAC_PI_2),
+				z_near: 0.5,
+			},
+			ScreenSize::new(100, 100),
+			None,
+		);
+		assert_relative_eq!(project(matrix, Vec3::new(0.5, 0.5, 0.5)), Vec3::new(1., 1., 0.), epsilon = 1e-5);
+		assert_relative_eq!(project(matrix, Vec3::new(0., 0., 1e6)).z, 1., epsilon = 1e-5);
+		assert!(project(matrix, Vec3::new(0., 0., 1e3)).z < 1.);
+	}
+
+	#[test]
+	fn projection_off_center() {
+		let matrix = calc_projection_matrix(
+			Projection::OffCenter(FrustumPlanes {
+				left: 0.,
+				right: 2.,
+				bottom: -1.,
+				top: 3.,
+				near: 1.,
+				far: 10.,
+			}),
+			ScreenSize::new(100, 100),
+			None,
+		);
+		assert_relative_eq!(project(matrix, Vec3::new(0., -1., 1.)), Vec3::new(-1., -1., 0.), epsilon = 1e-5);
+		assert_relative_eq!(project(matrix, Vec3::new(20., 30., 10.)), Vec3::new(1., 1., 1.), epsilon = 1e-5);
+	}
+
+	#[rstest]
+	#[case(Projection::Perspective(Frustum { y_fov: Radians::new(1.2), z_near: 0.1, z_far: 100. }))]
+	#[case(Projection::InfinitePerspective { y_fov: Radians::new(1.2), z_near: 0.1 })]
+	#[case(Projection::Orthographic { height: 10., z_near: 0.1, z_far: 100. })]
+	fn projection_jitter(#[case] projection: Projection) {
+		let size = ScreenSize::new(200, 100);
+		let matrix = calc_projection_matrix(projection, size, None);
+		let jittered = calc_projection_matrix(projection, size, Some(Vec2::new(0.5, 0.25)));
+
+		// Half a pixel right and a quarter of a pixel down, at any depth
+		for point in [Vec3::new(1., 2., 0.5), Vec3::new(-3., 1., 20.)] {
+			let offset = project(jittered, point) - project(matrix, point);
+			assert_relative_eq!(offset, Vec3::new(0.5 * 2. / 200., -0.25 * 2. / 100., 0.), epsilon = 1e-5);
+		}
+	}
+
+	#[rstest]
+	#[case(1, 2, 0.5)]
+	#[case(2, 2, 0.25)]
+	#[case(3, 2, 0.75)]
+	#[case(4, 2, 0.125)]
+	#[case(1, 3, 1. / 3.)]
+	#[case(2, 3, 2. / 3.)]
+	#[case(3, 3, 1. / 9.)]
+	#[case(0, 3, 0.)]
+	fn halton_sequence(#[case] index: u32, #[case] base: u32, #[case] expected: f32) {
+		assert_relative_eq!(halton(index, base), expected);
+	}
+
+	#[test]
+	fn jitter_sequence() {
+		let jitter = JitterSequence::new(4);
+		let offsets = jitter.take(8).collect::<Vec<_>>();
+		assert_relative_eq!(offsets[0], Vec2::new(0., 1. / 3. - 0.5));
+		assert_relative_eq!(offsets[1], Vec2::new(-0.25, 2. / 3. - 0.5));
+		assert_eq!(offsets[..4], offsets[4..]);
+		assert!(offsets.iter().all(|offset| offset.x.abs() <= 0.5 && offset.y.abs() <= 0.5));
+		assert_eq!(JitterSequence::default().length(), 8);
+	}
+
+	#[test]
+	#[should_panic(expected = "the jitter sequence must not be empty")]
+	fn jitter_sequence_empty() {
+		JitterSequence::new(0);
+	}
+
+	#[test]
+	fn view_projection_history() {
+		let frustum = Frustum {
+			y_fov: Radians::new(1.2),
+			z_near: 0.1,
+			z_far: 100.,
+		};
+		let projection = calc_projection_matrix(frustum, ScreenSize::new(100, 100), None);
+		let view_projection =
+			|x: f32| projection * calc_view_matrix(Position(Vec3::new(x, 0., 0.)), Direction::default());
+
+		let mut history = ViewProjectionHistory::new(view_projection(0.));
+		let point = Vec3::new(0., 0., 10.);
+		assert_eq!(history.motion_vector(point), Vec2::zero());
+
+		// The camera moving right makes the scene move left
+		history.push(view_projection(1.));
+		assert_eq!(history.previous(), view_projection(0.));
+		assert!(history.motion_vector(point).x < 0.);
+		assert_relative_eq!(history.motion_vector(point).y, 0.);
+
+		let reprojected = history.reprojection_matrix() * history.current() * point.with_w(1.);
+		let previous = history.previous() * point.with_w(1.);
+		assert_relative_eq!(reprojected / reprojected.w, previous / previous.w, epsilon = 1e-5);
+	}
+
+	#[rstest]
+	#[case(CameraConventions::WGPU)]
+	#[case(CameraConventions::OPENGL)]
+	#[case(CameraConventions { reversed_z: true, ..CameraConventions::WGPU })]
+	#[case(CameraConventions { reversed_z: true, ..CameraConventions::OPENGL })]
+	fn conventions_depth(#[case] conventions: CameraConventions) {
+		let frustum = Frustum {
+			y_fov: Radians::new(1.2),
+			z_near: 0.1,
+			z_far: 100.,
+		};
+		let matrix = conventions.projection_matrix(frustum, ScreenSize::new(1920, 1080), None);
+
+		let forward = match conventions.handedness {
+			Handedness::Left => 1.,
+			Handedness::Right => -1.,
+		};
+		let (near, far) = match (conventions.depth_range, conventions.reversed_z) {
+			(DepthRange::ZeroToOne, false) => (0., 1.),
+			(DepthRange::ZeroToOne, true) => (1., 0.),
+			(DepthRange::NegativeOneToOne, false) => (-1., 1.),
+			(DepthRange::NegativeOneToOne, true) => (1., -1.),
+		};
+
+		assert_relative_eq!(project(matrix, Vec3::new(0., 0., 0.1 * forward)).z, near, epsilon = 1e-4);
+		assert_relative_eq!(project(matrix, Vec3::new(0., 0., 100. * forward)).z, far, epsilon = 1e-4);
+	}
+
+	#[test]
+	fn conventions_match_vek() {
+		let size = ScreenSize::new(1920, 1080);
+		let frustum = Frustum {
+			y_fov: Radians::new(1.2),
+			z_near: 0.1,
+			z_far: 100.,
+		};
+		assert_eq!(
+			CameraConventions::default().projection_matrix(frustum, size, None),
+			calc_projection_matrix(frustum, size, None)
+		);
+		assert_relative_eq!(
+			CameraConventions::OPENGL.projection_matrix(frustum, size, None),
+			Mat4::perspective_fov_rh_no(1.2, 1920., 1080., 0.1, 100.),
+			epsilon = 1e-5
+		);
+	}
+
+	#[rstest]
+	#[case(CameraConventions::WGPU)]
+	#[case(CameraConventions::OPENGL)]
+	fn conventions_view(#[case] conventions: CameraConventions) {
+		let direction = Direction {
+			yaw: Degrees::new(30.).into(),
+			pitch: Degrees::new(20.).into(),
+		};
+		let position = Position(Vec3::new(1., 2., 3.));
+		let view = conventions.view_matrix(position, direction);
+
+		let forward = conventions.forward_vector(direction);
+		let right = conventions.right_vector(direction);
+		let up = conventions.up_vector(direction);
+
+		// Forward is along the view-space Z axis, whichever way it points
+		let z = match conventions.handedness {
+			Handedness::Left => 1.,
+			Handedness::Right => -1.,
+		};
+		assert_relative_eq!(view.mul_point(*position + forward), Vec3::new(0., 0., z), epsilon = 1e-5);
+		assert_relative_eq!(view.mul_point(*position + right), Vec3::new(1., 0., 0.), epsilon = 1e-5);
+		assert_relative_eq!(view.mul_point(*position + up), Vec3::new(0., 1., 0.), epsilon = 1e-5);
+	}
+
+	#[rstest]
+	#[case(0.,    0.)]
+	#[case(90.,   0.)]
+	#[case(-135., 30.)]
+	#[case(45.,   -80.)]
+	#[case(170.,  89.)]
+	fn direction_from_forward(#[case] yaw: f32, #[case] pitch: f32) {
+		let direction = Direction {
+			yaw: Degrees::new(yaw).into(),
+			pitch: Degrees::new(pitch).into(),
+		};
+		let forward = calc_forward_vector(direction);
+
+		let result = Direction::from_forward(forward * 3.);
+		assert_relative_eq!(calc_forward_vector(result), forward, epsilon = 1e-5);
+		assert_relative_eq!(result.yaw.sin_cos().0, direction.yaw.sin_cos().0, epsilon = 1e-5);
+		assert_relative_eq!(result.yaw.sin_cos().1, direction.yaw.sin_cos().1, epsilon = 1e-5);
+		assert_relative_eq!(result.pitch.radians(), direction.pitch.radians(), epsilon = 1e-5);
+	}
+
+	#[rstest]
+	#[case(Vec3::new(0., 1., 0.),   SAFE_FRAC_PI_2)]
+	#[case(Vec3::new(0., -5., 0.),  -SAFE_FRAC_PI_2)]
+	fn direction_from_vertical(#[case] forward: Vec3<f32>, #[case] pitch: f32) {
+		let direction = Direction::from_forward(forward);
+		assert_relative_eq!(direction.yaw.radians(), 0.);
+		assert_relative_eq!(direction.pitch.radians(), pitch);
+		assert!(calc_view_matrix(Position::default(), direction).into_col_array().iter().all(|v| v.is_finite()));
+
+		assert_eq!(Direction::from_forward(Vec3::zero()), Direction::default());
+	}
+
+	#[test]
+	fn direction_look_at() {
+		let direction = Direction::look_at(Position(Vec3::new(1., 1., 1.)), Vec3::new(2., 1., 1.));
+		assert_relative_eq!(direction.yaw.radians(), consts::FRAC_PI_2);
+		assert_relative_eq!(direction.pitch.radians(), 0.);
+	}
+
+	#[rstest]
+	#[case(0.,    0.)]
+	#[case(90.,   45.)]
+	#[case(-135., -30.)]
+	#[case(60.,   -90.)]
+	#[case(-60.,  90.)]
+	fn direction_quaternion(#[case] yaw: f32, #[case] pitch: f32) {
+		let direction = Direction {
+			yaw: Degrees::new(yaw).into(),
+			pitch: Degrees::new(pitch).into(),
+		};
+		let rotation = Quaternion::from(direction);
+		assert_relative_eq!(rotation * Vec3::unit_z(), calc_forward_vector(direction), epsilon = 1e-5);
+		assert_relative_eq!(rotation * Vec3::unit_y(), calc_up_vector(direction), epsilon = 1e-5);
+		assert_relative_eq!(rotation * Vec3::unit_x(), calc_right_vector(direction), epsilon = 1e-5);
+
+		// Even straight up or down, the yaw survives the roundtrip
+		let result = Direction::from(rotation);
+		assert_relative_eq!(result.yaw.sin_cos().0, direction.yaw.sin_cos().0, epsilon = 1e-4);
+		assert_relative_eq!(result.yaw.sin_cos().1, direction.yaw.sin_cos().1, epsilon = 1e-4);
+		let pitch = direction.pitch.radians().clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2);
+		assert_relative_eq!(result.pitch.radians(), pitch, epsilon = 1e-4);
+	}
+
+	#[rstest]
+	#[case(ScreenSize::new(600, 600),   90., 90.)]
+	#[case(ScreenSize::new(1920, 1080), 60., 91.492_94)]
+	#[case(ScreenSize::new(1080, 1920), 60., 35.983_4)]
+	fn fov_conversion(#[case] size: ScreenSize, #[case] y_fov: f32, #[case] x_fov: f32) {
+		assert_relative_eq!(calc_x_fov(Degrees::new(y_fov), size).degrees(), x_fov, epsilon = 1e-3);
+		assert_relative_eq!(calc_y_fov(Degrees::new(x_fov), size).degrees(), y_fov, epsilon = 1e-3);
+
+		let frustum = Frustum::from_x_fov(Degrees::new(x_fov), size, 0.1, 100.);
+		assert_relative_eq!(frustum.y_fov.degrees(), y_fov, epsilon = 1e-3);
+		assert_relative_eq!(frustum.x_fov(size).degrees(), x_fov, epsilon = 1e-3);
+	}
+
+	#[test]
+	fn frustum_focal_length() {
+		let size = ScreenSize::new(1920, 1080);
+
+		let vertical = Frustum::from_focal_length(50., FULL_FRAME_SENSOR, SensorFit::Vertical, size, 0.1, 100.);
+		assert_relative_eq!(vertical.y_fov.radians(), (12f32 / 50.).atan() * 2.);
+		assert_relative_eq!(vertical.focal_length(FULL_FRAME_SENSOR.h), 50., epsilon = 1e-4);
+
+		let horizontal = Frustum::from_focal_length(50., FULL_FRAME_SENSOR, SensorFit::Horizontal, size, 0.1, 100.);
+		assert_relative_eq!(horizontal.x_fov(size).radians(), (18f32 / 50.).atan() * 2., epsilon = 1e-6);
+	}
+
+	#[test]
+	fn frustum_degrees() {
+		let frustum = Frustum::new(Degrees::new(90.), 0.1, 100.);
+		assert_relative_eq!(frustum.y_fov.radians(), consts::FRAC_PI_2);
+	}
 }
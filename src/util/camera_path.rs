@@ -0,0 +1,372 @@
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use vek::Vec3;
+
+use crate::{Direction, Frustum, Position, Radians, Speed, Tweenable};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A camera pose at a point in time of a [`CameraPath`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraKeyframe {
+	pub time: Duration,
+	pub position: Position,
+	pub direction: Direction,
+	pub y_fov: Radians,
+	/// The velocity of the camera at this keyframe, in units per second, used
+	/// by [`PathInterpolation::Hermite`]. [`None`] computes it from the
+	/// neighbouring keyframes.
+	pub tangent: Option<Vec3<f32>>,
+}
+
+/// How the positions are interpolated between keyframes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PathInterpolation {
+	/// Goes smoothly through every keyframe, ignoring the tangents.
+	#[default]
+	CatmullRom,
+	/// Uses the keyframe tangents, giving control over the speed and the shape
+	/// of the path around each keyframe.
+	Hermite,
+}
+
+/// An interpolated camera pose.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraPathSample {
+	pub position: Position,
+	pub direction: Direction,
+	pub y_fov: Radians,
+}
+
+/// A smooth camera motion through keyframes, for cutscenes and fly-throughs.
+///
+/// The path can be followed either with the timing of the keyframes, or at a
+/// constant speed along the path. The directions are interpolated along the
+/// shortest arc, and the field of view linearly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraPath {
+	keyframes: Vec<CameraKeyframe>,
+	interpolation: PathInterpolation,
+	/// The cumulative arc length at each subdivision of each segment.
+	arc_lengths: Vec<f32>,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl CameraKeyframe {
+	pub fn new(time: Duration, position: Position, direction: Direction, y_fov: impl Into<Radians>) -> Self {
+		Self {
+			time,
+			position,
+			direction,
+			y_fov: y_fov.into(),
+			tangent: None,
+		}
+	}
+
+	pub fn with_tangent(mut self, tangent: Vec3<f32>) -> Self {
+		self.tangent = Some(tangent);
+		self
+	}
+}
+
+impl CameraPathSample {
+	/// The frustum with the field of view of the sample.
+	pub fn frustum(&self, frustum: Frustum) -> Frustum {
+		Frustum {
+			y_fov: self.y_fov,
+			..frustum
+		}
+	}
+}
+
+impl CameraPath {
+	/// The number of straight lines each segment is approximated with to
+	/// measure its length.
+	const SUBDIVISIONS: usize = 32;
+
+	/// Creates a path going through the keyframes, sorted by time.
+	///
+	/// # Panics
+	///
+	/// If `keyframes` is empty.
+	pub fn new(mut keyframes: Vec<CameraKeyframe>, interpolation: PathInterpolation) -> Self {
+		assert!(!keyframes.is_empty(), "a camera path needs at least one keyframe");
+		keyframes.sort_by_key(|keyframe| keyframe.time);
+
+		let mut path = Self {
+			keyframes,
+			interpolation,
+			arc_lengths: Vec::new(),
+		};
+		path.arc_lengths = path.calc_arc_lengths();
+		path
+	}
+
+	pub fn keyframes(&self) -> &[CameraKeyframe] {
+		&self.keyframes
+	}
+
+	pub fn interpolation(&self) -> PathInterpolation {
+		self.interpolation
+	}
+
+	/// The time of the last keyframe.
+	pub fn duration(&self) -> Duration {
+		self.keyframes[self.keyframes.len() - 1].time
+	}
+
+	/// The approximate length of the path.
+	pub fn length(&self) -> f32 {
+		self.arc_lengths.last().copied().unwrap_or(0.)
+	}
+
+	/// Samples the path following the timing of the keyframes. The path is
+	/// clamped before the first and after the last keyframe.
+	pub fn sample(&self, time: Duration) -> CameraPathSample {
+		let segment = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+
+		if segment == 0 {
+			return self.sample_segment(0, 0.);
+		}
+		if segment >= self.keyframes.len() {
+			return self.sample_segment(self.keyframes.len() - 1, 0.);
+		}
+
+		let (start, end) = (self.keyframes[segment - 1].time, self.keyframes[segment].time);
+		let t = (time - start).as_secs_f32() / (end - start).as_secs_f32();
+		self.sample_segment(segment - 1, t)
+	}
+
+	/// Samples the path at a distance along it, clamped to the length of the
+	/// path.
+	pub fn sample_at_distance(&self, distance: f32) -> CameraPathSample {
+		if self.keyframes.len() == 1 {
+			return self.sample_segment(0, 0.);
+		}
+
+		let distance = distance.clamp(0., self.length());
+		let index = self.arc_lengths.partition_point(|&length| length < distance).max(1);
+		let (before, after) = (self.arc_lengths[index - 1], self.arc_lengths[index]);
+		let fraction = if after > before {
+			(distance - before) / (after - before)
+		} else {
+			0.
+		};
+
+		let subdivision = (index - 1) as f32 + fraction;
+		let segment = ((index - 1) / Self::SUBDIVISIONS).min(self.keyframes.len() - 2);
+		self.sample_segment(segment, subdivision / Self::SUBDIVISIONS as f32 - segment as f32)
+	}
+
+	/// Samples the path as if travelled at a constant speed from the first
+	/// keyframe, ignoring the timing of the keyframes.
+	pub fn sample_at_speed(&self, speed: Speed<f32>, time: Duration) -> CameraPathSample {
+		self.sample_at_distance(speed * time)
+	}
+
+	fn sample_segment(&self, segment: usize, t: f32) -> CameraPathSample {
+		let start = &self.keyframes[segment];
+		let Some(end) = self.keyframes.get(segment + 1) else {
+			return CameraPathSample {
+				position: start.position,
+				direction: start.direction,
+				y_fov: start.y_fov,
+			};
+		};
+
+		CameraPathSample {
+			position: Position(self.calc_position(segment, t)),
+			direction: Direction::tween(start.direction, end.direction, t),
+			y_fov: start.y_fov + (end.y_fov - start.y_fov) * t,
+		}
+	}
+
+	/// The cubic Hermite interpolation of the positions of a segment.
+	fn calc_position(&self, segment: usize, t: f32) -> Vec3<f32> {
+		let (p0, p1) = (*self.keyframes[segment].position, *self.keyframes[segment + 1].position);
+		let (m0, m1) = (self.calc_tangent(segment, segment), self.calc_tangent(segment + 1, segment));
+
+		let (t2, t3) = (t * t, t * t * t);
+		p0 * (2. * t3 - 3. * t2 + 1.) + m0 * (t3 - 2. * t2 + t) + p1 * (-2. * t3 + 3. * t2) + m1 * (t3 - t2)
+	}
+
+	/// The tangent at a keyframe, scaled for the given segment.
+	fn calc_tangent(&self, index: usize, segment: usize) -> Vec3<f32> {
+		let last = self.keyframes.len() - 1;
+		let (previous, next) = (&self.keyframes[index.saturating_sub(1)], &self.keyframes[(index + 1).min(last)]);
+
+		match self.interpolation {
+			// The end points are duplicated, so the tangent there is half of the
+			// segment
+			PathInterpolation::CatmullRom => (*next.position - *previous.position) / 2.,
+			PathInterpolation::Hermite => {
+				let segment_duration = (self.keyframes[segment + 1].time - self.keyframes[segment].time).as_secs_f32();
+
+				let velocity = self.keyframes[index].tangent.unwrap_or_else(|| {
+					let duration = (next.time - previous.time).as_secs_f32();
+					if duration > 0. {
+						(*next.position - *previous.position) / duration
+					} else {
+						Vec3::zero()
+					}
+				});
+				velocity * segment_duration
+			}
+		}
+	}
+
+	fn calc_arc_lengths(&self) -> Vec<f32> {
+		let mut lengths = vec![0.];
+		let mut total = 0.;
+
+		for segment in 0..self.keyframes.len() - 1 {
+			let mut previous = self.calc_position(segment, 0.);
+			for i in 1..=Self::SUBDIVISIONS {
+				let point = self.calc_position(segment, i as f32 / Self::SUBDIVISIONS as f32);
+				total += point.distance(previous);
+				lengths.push(total);
+				previous = point;
+			}
+		}
+
+		lengths
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{AngleDegreesType, AngleRadiansType, Degrees};
+
+	fn keyframe(secs: f32, position: Vec3<f32>, yaw: f32) -> CameraKeyframe {
+		let direction = Direction {
+			yaw: Degrees::new(yaw).into(),
+			pitch: Degrees::new(0.).into(),
+		};
+		CameraKeyframe::new(Duration::from_secs_f32(secs), Position(position), direction, Radians::new(1.))
+	}
+
+	fn path(interpolation: PathInterpolation) -> CameraPath {
+		CameraPath::new(
+			vec![
+				keyframe(0., Vec3::new(0., 0., 0.), 0.),
+				keyframe(1., Vec3::new(10., 0., 0.), 90.),
+				keyframe(3., Vec3::new(10., 5., 10.), 180.),
+				keyframe(4., Vec3::new(0., 0., 20.), 270.),
+			],
+			interpolation,
+		)
+	}
+
+	#[rstest]
+	#[case(PathInterpolation::CatmullRom)]
+	#[case(PathInterpolation::Hermite)]
+	fn goes_through_keyframes(#[case] interpolation: PathInterpolation) {
+		let path = path(interpolation);
+		for keyframe in path.keyframes() {
+			let sample = path.sample(keyframe.time);
+			assert_relative_eq!(*sample.position, *keyframe.position, epsilon = 1e-5);
+			assert_relative_eq!(sample.direction.yaw.degrees(), keyframe.direction.yaw.degrees(), epsilon = 1e-3);
+		}
+
+		assert_eq!(path.sample(Duration::from_secs(10)).position, path.keyframes()[3].position);
+		assert_eq!(path.duration(), Duration::from_secs(4));
+	}
+
+	#[test]
+	fn catmull_rom_straight_line() {
+		let path = CameraPath::new(
+			(0..4).map(|i| keyframe(i as f32, Vec3::new(i as f32, 0., 0.), 0.)).collect(),
+			PathInterpolation::CatmullRom,
+		);
+		assert_relative_eq!(*path.sample(Duration::from_secs_f32(1.25)).position, Vec3::new(1.25, 0., 0.));
+		assert_relative_eq!(path.length(), 3., epsilon = 1e-4);
+	}
+
+	#[test]
+	fn hermite_tangents() {
+		let keyframes = vec![
+			keyframe(0., Vec3::new(0., 0., 0.), 0.).with_tangent(Vec3::zero()),
+			keyframe(2., Vec3::new(4., 0., 0.), 0.).with_tangent(Vec3::zero()),
+		];
+		let path = CameraPath::new(keyframes, PathInterpolation::Hermite);
+
+		// Eases in and out
+		assert_relative_eq!(*path.sample(Duration::from_secs(1)).position, Vec3::new(2., 0., 0.));
+		assert!(path.sample(Duration::from_secs_f32(0.2)).position.x < 0.4 * 0.5);
+	}
+
+	#[test]
+	fn shortest_arc() {
+		let path = CameraPath::new(
+			vec![
+				keyframe(0., Vec3::zero(), 350.),
+				keyframe(1., Vec3::zero(), 10.),
+			],
+			PathInterpolation::CatmullRom,
+		);
+		let yaw = path.sample(Duration::from_millis(500)).direction.yaw.degrees();
+		assert_relative_eq!(yaw.rem_euclid(360.), 0., epsilon = 1e-3);
+	}
+
+	#[test]
+	fn field_of_view() {
+		let mut keyframes = vec![keyframe(0., Vec3::zero(), 0.), keyframe(2., Vec3::zero(), 0.)];
+		keyframes[1].y_fov = Radians::new(2.);
+		let path = CameraPath::new(keyframes, PathInterpolation::CatmullRom);
+
+		let sample = path.sample(Duration::from_millis(500));
+		assert_relative_eq!(sample.y_fov.radians(), 1.25);
+
+		let frustum = Frustum::new(Degrees::new(60.), 0.1, 10.);
+		assert_eq!(sample.frustum(frustum).y_fov, sample.y_fov);
+	}
+
+	#[rstest]
+	#[case(PathInterpolation::CatmullRom)]
+	#[case(PathInterpolation::Hermite)]
+	fn constant_speed(#[case] interpolation: PathInterpolation) {
+		let path = path(interpolation);
+		let speed = Speed::new_per_second(5.);
+
+		let steps = 50;
+		let step = Duration::from_secs_f32(path.length() / speed.per_second() / steps as f32);
+		let points = (0..=steps)
+			.map(|i| *path.sample_at_speed(speed, step * i).position)
+			.collect::<Vec<_>>();
+
+		let expected = path.length() / steps as f32;
+		for pair in points.windows(2) {
+			assert_relative_eq!(pair[0].distance(pair[1]), expected, max_relative = 0.02);
+		}
+
+		assert_relative_eq!(points[0], *path.keyframes()[0].position);
+		assert_relative_eq!(points[steps as usize], *path.keyframes()[3].position, epsilon = 1e-3);
+	}
+
+	#[test]
+	#[should_panic(expected = "a camera path needs at least one keyframe")]
+	fn no_keyframes() {
+		CameraPath::new(Vec::new(), PathInterpolation::default());
+	}
+}
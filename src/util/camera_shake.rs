@@ -0,0 +1,347 @@
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use vek::Vec3;
+
+use crate::{
+	calc_forward_vector, calc_right_vector, calc_up_vector, Angle, Direction, Position, Radians, SAFE_FRAC_PI_2,
+};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A trauma-based camera shake, following the approach from Squirrel Eiserloh's
+/// "Juicing Your Cameras With Math" talk.
+///
+/// Events add trauma, which decays linearly over time. The shake intensity is
+/// the trauma raised to `exponent`, so that small amounts of trauma barely
+/// shake while large amounts shake a lot. Each axis follows its own smooth
+/// noise, seeded deterministically.
+///
+/// The shake doesn't modify the camera: [`apply`](Self::apply) offsets the
+/// output of any controller, and that offset state must not be fed back into
+/// the controller.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraShake {
+	/// The largest offsets, reached at full trauma.
+	pub amplitude: ShakeOffset,
+	/// How many times per second the noise changes direction, roughly.
+	pub frequency: f32,
+	/// The time it takes for the trauma to decay from 1 to 0.
+	pub decay: Duration,
+	pub exponent: f32,
+	trauma: f32,
+	time: f32,
+	noise: [Noise; 6],
+}
+
+/// An offset to apply on top of a camera position and direction.
+///
+/// The translation is relative to the camera: X is right, Y is up and Z is
+/// forward. The roll isn't used by [`Direction`], but is available to build
+/// rolled view matrices.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ShakeOffset {
+	pub translation: Vec3<f32>,
+	pub yaw: Angle,
+	pub pitch: Angle,
+	pub roll: Angle,
+}
+
+/// One-dimensional smooth value noise in [-1, 1], repeating after
+/// [`Noise::PERIOD`].
+#[derive(Clone, Debug, PartialEq)]
+struct Noise {
+	values: [f32; Noise::PERIOD],
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl CameraShake {
+	/// A shake with sensible defaults for a first-person camera: no translation,
+	/// up to 5° of rotation, 15 Hz and a one second decay.
+	pub fn new(seed: u64) -> Self {
+		let mut rng = StdRng::seed_from_u64(seed);
+
+		Self {
+			amplitude: ShakeOffset {
+				translation: Vec3::zero(),
+				yaw: Radians::new(5f32.to_radians()),
+				pitch: Radians::new(5f32.to_radians()),
+				roll: Radians::new(5f32.to_radians()),
+			},
+			frequency: 15.,
+			decay: Duration::from_secs(1),
+			exponent: 2.,
+			trauma: 0.,
+			time: 0.,
+			noise: [(); 6].map(|_| Noise::new(&mut rng)),
+		}
+	}
+
+	pub fn with_amplitude(mut self, amplitude: ShakeOffset) -> Self {
+		self.amplitude = amplitude;
+		self
+	}
+
+	pub fn with_frequency(mut self, frequency: f32) -> Self {
+		self.frequency = frequency;
+		self
+	}
+
+	pub fn with_decay(mut self, decay: Duration) -> Self {
+		self.decay = decay;
+		self
+	}
+
+	pub fn with_exponent(mut self, exponent: f32) -> Self {
+		self.exponent = exponent;
+		self
+	}
+
+	pub fn trauma(&self) -> f32 {
+		self.trauma
+	}
+
+	/// Sets the trauma, clamped to [0, 1].
+	pub fn set_trauma(&mut self, trauma: f32) {
+		self.trauma = trauma.clamp(0., 1.);
+	}
+
+	/// Adds trauma, for instance when getting hit. The total is clamped to 1.
+	pub fn add_trauma(&mut self, amount: f32) {
+		self.set_trauma(self.trauma + amount);
+	}
+
+	/// The current shake intensity, from 0 to 1.
+	pub fn intensity(&self) -> f32 {
+		self.trauma.powf(self.exponent)
+	}
+
+	/// Advances the noise and decays the trauma for a frame lasting `delta`.
+	pub fn update(&mut self, delta: Duration) {
+		self.time += delta.as_secs_f32();
+		// Keeps the time precise, the noise repeats anyway
+		self.time %= Noise::PERIOD as f32 / self.frequency;
+
+		if self.decay.is_zero() {
+			self.trauma = 0.;
+		} else {
+			self.set_trauma(self.trauma - delta.as_secs_f32() / self.decay.as_secs_f32());
+		}
+	}
+
+	/// The current offset.
+	pub fn offset(&self) -> ShakeOffset {
+		let intensity = self.intensity();
+		let t = self.time * self.frequency;
+		let [x, y, z, yaw, pitch, roll] = [0, 1, 2, 3, 4, 5].map(|i| self.noise[i].sample(t) * intensity);
+
+		ShakeOffset {
+			translation: self.amplitude.translation * Vec3::new(x, y, z),
+			yaw: self.amplitude.yaw * yaw,
+			pitch: self.amplitude.pitch * pitch,
+			roll: self.amplitude.roll * roll,
+		}
+	}
+
+	/// Shakes a camera position and direction, usually the output of a
+	/// controller.
+	pub fn apply(&self, position: Position, direction: Direction) -> (Position, Direction) {
+		self.offset().apply(position, direction)
+	}
+}
+
+impl ShakeOffset {
+	/// Offsets a camera position and direction. The pitch stays within
+	/// [`SAFE_FRAC_PI_2`], so a camera looking straight up or down doesn't flip
+	/// over.
+	pub fn apply(&self, position: Position, direction: Direction) -> (Position, Direction) {
+		let translation = calc_right_vector(direction) * self.translation.x
+			+ calc_up_vector(direction) * self.translation.y
+			+ calc_forward_vector(direction) * self.translation.z;
+
+		let mut pitch = direction.pitch + self.pitch;
+		pitch.clamp(Radians::new(-SAFE_FRAC_PI_2), Radians::new(SAFE_FRAC_PI_2));
+
+		(
+			Position(*position + translation),
+			Direction {
+				yaw: direction.yaw + self.yaw,
+				pitch,
+			},
+		)
+	}
+}
+
+impl Noise {
+	const PERIOD: usize = 256;
+
+	fn new(rng: &mut impl Rng) -> Self {
+		Self {
+			values: [(); Self::PERIOD].map(|_| rng.gen_range(-1. ..=1.)),
+		}
+	}
+
+	fn sample(&self, t: f32) -> f32 {
+		let floor = t.floor();
+		let index = (floor as i64).rem_euclid(Self::PERIOD as i64) as usize;
+		let (a, b) = (self.values[index], self.values[(index + 1) % Self::PERIOD]);
+
+		// Smoothstep, so that the noise has no sharp corners at the lattice points
+		let fraction = t - floor;
+		let fraction = fraction * fraction * (3. - 2. * fraction);
+		a + (b - a) * fraction
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{AngleRadiansType, Degrees};
+
+	fn shake(seed: u64) -> CameraShake {
+		let mut shake = CameraShake::new(seed).with_amplitude(ShakeOffset {
+			translation: Vec3::new(0.1, 0.2, 0.3),
+			yaw: Degrees::new(5.).into(),
+			pitch: Degrees::new(5.).into(),
+			roll: Degrees::new(5.).into(),
+		});
+		shake.add_trauma(1.);
+		shake
+	}
+
+	fn offsets(mut shake: CameraShake) -> Vec<ShakeOffset> {
+		(0..10)
+			.map(|_| {
+				shake.update(Duration::from_millis(16));
+				shake.offset()
+			})
+			.collect()
+	}
+
+	#[test]
+	fn deterministic() {
+		assert_eq!(offsets(shake(42)), offsets(shake(42)));
+		assert_ne!(offsets(shake(42)), offsets(shake(43)));
+	}
+
+	#[test]
+	fn no_trauma_no_shake() {
+		let mut shake = shake(0);
+		shake.set_trauma(0.);
+		shake.update(Duration::from_millis(100));
+		assert_eq!(shake.offset(), ShakeOffset::default());
+	}
+
+	#[rstest]
+	#[case(0,    1.)]
+	#[case(250,  0.75)]
+	#[case(500,  0.5)]
+	#[case(1000, 0.)]
+	#[case(2000, 0.)]
+	fn trauma_decay(#[case] millis: u64, #[case] expected: f32) {
+		let mut shake = shake(0);
+		shake.update(Duration::from_millis(millis));
+		assert_relative_eq!(shake.trauma(), expected, epsilon = 1e-6);
+		assert_relative_eq!(shake.intensity(), expected * expected, epsilon = 1e-6);
+	}
+
+	#[test]
+	fn trauma_is_clamped() {
+		let mut shake = shake(0);
+		shake.add_trauma(0.5);
+		assert_eq!(shake.trauma(), 1.);
+		shake.add_trauma(-3.);
+		assert_eq!(shake.trauma(), 0.);
+	}
+
+	#[test]
+	fn bounded_by_amplitude() {
+		let mut shake = shake(7).with_decay(Duration::from_secs(1000));
+		for _ in 0..1000 {
+			shake.update(Duration::from_millis(7));
+			let offset = shake.offset();
+			assert!(offset.translation.x.abs() <= 0.1);
+			assert!(offset.translation.y.abs() <= 0.2);
+			assert!(offset.translation.z.abs() <= 0.3);
+			assert!(offset.yaw.radians().abs() <= 5f32.to_radians() + 1e-6);
+		}
+	}
+
+	#[test]
+	fn smooth() {
+		let mut shake = shake(3).with_decay(Duration::from_secs(1000));
+		let mut previous = shake.offset();
+		for _ in 0..1000 {
+			shake.update(Duration::from_millis(1));
+			let offset = shake.offset();
+			// At most 1.5 times the slope of the linear interpolation (smoothstep)
+			let max_change = 0.3 * 2. * 1.5 * shake.frequency * 0.001;
+			assert!((offset.translation.z - previous.translation.z).abs() <= max_change + 1e-6);
+			previous = offset;
+		}
+	}
+
+	#[rstest]
+	#[case(SAFE_FRAC_PI_2)]
+	#[case(-SAFE_FRAC_PI_2)]
+	fn pitch_stays_clamped(#[case] pitch: f32) {
+		let direction = Direction {
+			yaw: Radians::new(0.),
+			pitch: Radians::new(pitch),
+		};
+		let offset = ShakeOffset {
+			pitch: Degrees::new(5. * pitch.signum()).into(),
+			..ShakeOffset::default()
+		};
+
+		let (_, shaken) = offset.apply(Position(Vec3::zero()), direction);
+		assert_relative_eq!(shaken.pitch.radians(), pitch);
+		// Still looking the same way, not over the pole
+		assert!(calc_forward_vector(shaken).dot(calc_forward_vector(direction)) > 0.99);
+	}
+
+	#[test]
+	fn composes_with_controller() {
+		let position = Position(Vec3::new(1., 2., 3.));
+		let direction = Direction {
+			yaw: Degrees::new(90.).into(),
+			pitch: Degrees::new(10.).into(),
+		};
+
+		let offset = ShakeOffset {
+			translation: Vec3::new(1., 0., 2.),
+			yaw: Degrees::new(2.).into(),
+			pitch: Degrees::new(-1.).into(),
+			roll: Degrees::new(0.).into(),
+		};
+		let (shaken_position, shaken_direction) = offset.apply(position, direction);
+
+		// Looking towards +X, right is -Z
+		let (sin, cos) = 10f32.to_radians().sin_cos();
+		assert_relative_eq!(*shaken_position, Vec3::new(1. + 2. * cos, 2. + 2. * sin, 2.), epsilon = 1e-5);
+		assert_relative_eq!(shaken_direction.yaw.radians(), 92f32.to_radians(), epsilon = 1e-6);
+		assert_relative_eq!(shaken_direction.pitch.radians(), 9f32.to_radians(), epsilon = 1e-6);
+
+		assert_eq!(ShakeOffset::default().apply(position, direction), (position, direction));
+	}
+}
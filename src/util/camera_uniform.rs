@@ -0,0 +1,168 @@
+#![allow(dead_code)]
+
+use bytemuck::{Pod, Zeroable};
+use vek::Mat4;
+
+use crate::{calc_projection_matrix, calc_view_matrix, Direction, Position, Projection, ScreenSize};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// The camera data for a GPU uniform buffer, laid out to match
+/// [`CameraUniform::WGSL`] (which also satisfies std140).
+///
+/// The matrices are column-major, like WGSL and GLSL expect them.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct CameraUniform {
+	pub view: [[f32; 4]; 4],
+	pub projection: [[f32; 4]; 4],
+	pub view_projection: [[f32; 4]; 4],
+	pub inverse_view: [[f32; 4]; 4],
+	pub inverse_projection: [[f32; 4]; 4],
+	pub inverse_view_projection: [[f32; 4]; 4],
+	pub position: [f32; 3],
+	pub z_near: f32,
+	pub z_far: f32,
+	_padding: f32,
+	pub viewport_size: [f32; 2],
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl CameraUniform {
+	/// The matching WGSL struct declaration.
+	pub const WGSL: &'static str = "\
+struct CameraUniform {
+	view: mat4x4<f32>,
+	projection: mat4x4<f32>,
+	view_projection: mat4x4<f32>,
+	inverse_view: mat4x4<f32>,
+	inverse_projection: mat4x4<f32>,
+	inverse_view_projection: mat4x4<f32>,
+	position: vec3<f32>,
+	z_near: f32,
+	z_far: f32,
+	viewport_size: vec2<f32>,
+}
+";
+
+	pub fn new(position: Position, direction: Direction, projection: impl Into<Projection>, size: ScreenSize) -> Self {
+		let projection = projection.into();
+		Self::from_matrices(
+			position,
+			calc_view_matrix(position, direction),
+			calc_projection_matrix(projection, size, None),
+			projection.z_near(),
+			projection.z_far(),
+			size,
+		)
+	}
+
+	/// Builds the uniform from already computed matrices, for instance ones
+	/// using other [`CameraConventions`](crate::CameraConventions).
+	pub fn from_matrices(
+		Position(position): Position,
+		view: Mat4<f32>,
+		projection: Mat4<f32>,
+		z_near: f32,
+		z_far: f32,
+		size: ScreenSize,
+	) -> Self {
+		let view_projection = projection * view;
+
+		Self {
+			view: view.into_col_arrays(),
+			projection: projection.into_col_arrays(),
+			view_projection: view_projection.into_col_arrays(),
+			inverse_view: view.inverted().into_col_arrays(),
+			inverse_projection: projection.inverted().into_col_arrays(),
+			inverse_view_projection: view_projection.inverted().into_col_arrays(),
+			position: position.into_array(),
+			z_near,
+			z_far,
+			_padding: 0.,
+			viewport_size: size.as_::<f32>().into_array(),
+		}
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use std::mem::{align_of, offset_of, size_of};
+
+	use approx::assert_relative_eq;
+	use vek::{Vec3, Vec4};
+
+	use super::*;
+	use crate::{Degrees, Frustum, Radians};
+
+	#[test]
+	fn layout() {
+		// Offsets as computed by the WGSL uniform address space rules: mat4x4 and
+		// vec3 align to 16 bytes, the f32 after a vec3 fills its last 4 bytes and
+		// vec2 aligns to 8 bytes. The struct size rounds up to 16 bytes.
+		assert_eq!(offset_of!(CameraUniform, view), 0);
+		assert_eq!(offset_of!(CameraUniform, projection), 64);
+		assert_eq!(offset_of!(CameraUniform, view_projection), 128);
+		assert_eq!(offset_of!(CameraUniform, inverse_view), 192);
+		assert_eq!(offset_of!(CameraUniform, inverse_projection), 256);
+		assert_eq!(offset_of!(CameraUniform, inverse_view_projection), 320);
+		assert_eq!(offset_of!(CameraUniform, position), 384);
+		assert_eq!(offset_of!(CameraUniform, z_near), 396);
+		assert_eq!(offset_of!(CameraUniform, z_far), 400);
+		assert_eq!(offset_of!(CameraUniform, viewport_size), 408);
+		assert_eq!(size_of::<CameraUniform>(), 416);
+		assert_eq!(size_of::<CameraUniform>() % 16, 0);
+		assert_eq!(align_of::<CameraUniform>(), 4);
+
+		let uniform = CameraUniform::default();
+		assert_eq!(bytemuck::bytes_of(&uniform).len(), 416);
+	}
+
+	#[test]
+	fn matrices() {
+		let position = Position(Vec3::new(1., 2., 3.));
+		let direction = Direction {
+			yaw: Degrees::new(30.).into(),
+			pitch: Degrees::new(-10.).into(),
+		};
+		let frustum = Frustum {
+			y_fov: Radians::new(1.),
+			z_near: 0.1,
+			z_far: 50.,
+		};
+		let size = ScreenSize::new(1280, 720);
+		let uniform = CameraUniform::new(position, direction, frustum, size);
+
+		let view = Mat4::from_col_arrays(uniform.view);
+		let projection = Mat4::from_col_arrays(uniform.projection);
+		assert_eq!(view, calc_view_matrix(position, direction));
+		assert_eq!(projection, calc_projection_matrix(frustum, size, None));
+		assert_relative_eq!(Mat4::from_col_arrays(uniform.view_projection), projection * view);
+
+		let identity = Mat4::from_col_arrays(uniform.inverse_view_projection) * projection * view;
+		assert_relative_eq!(identity, Mat4::identity(), epsilon = 1e-4);
+
+		// The inverse view matrix moves the origin to the camera position
+		let origin = Mat4::from_col_arrays(uniform.inverse_view) * Vec4::new(0., 0., 0., 1.);
+		assert_relative_eq!(origin.xyz(), *position, epsilon = 1e-5);
+
+		assert_eq!(uniform.position, [1., 2., 3.]);
+		assert_eq!((uniform.z_near, uniform.z_far), (0.1, 50.));
+		assert_eq!(uniform.viewport_size, [1280., 720.]);
+	}
+}
@@ -0,0 +1,258 @@
+#![allow(dead_code)]
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use vek::{Mat4, Vec2, Vec3};
+
+use crate::{CameraConventions, Direction, Frustum, Handedness, Position, Radians};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A face of a cubemap, in the order of the layers of a wgpu cube texture.
+///
+/// The faces follow the usual cubemap orientation (shared by wgpu, Vulkan and
+/// DirectX): seen from the center of the cube, +X and -X have +Y up, +Y has -Z
+/// up and -Y has +Z up, +Z and -Z have +Y up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CubeFace {
+	PositiveX,
+	NegativeX,
+	PositiveY,
+	NegativeY,
+	PositiveZ,
+	NegativeZ,
+}
+
+/// Everything needed to render one face of a cubemap.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CubemapView {
+	pub face: CubeFace,
+	pub frustum: Frustum,
+	/// The direction of the face. Looking straight up or down has an exact 90°
+	/// pitch, which [`calc_view_matrix`](crate::calc_view_matrix) can't handle,
+	/// so use `view_matrix` instead.
+	pub direction: Direction,
+	pub view_matrix: Mat4<f32>,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl CubeFace {
+	/// All the faces, in layer order.
+	pub const ALL: [CubeFace; 6] = [
+		CubeFace::PositiveX,
+		CubeFace::NegativeX,
+		CubeFace::PositiveY,
+		CubeFace::NegativeY,
+		CubeFace::PositiveZ,
+		CubeFace::NegativeZ,
+	];
+
+	/// The index of the face's layer in a cube texture.
+	pub fn layer(self) -> u32 {
+		self as u32
+	}
+
+	pub fn forward_vector(self) -> Vec3<f32> {
+		match self {
+			CubeFace::PositiveX => Vec3::unit_x(),
+			CubeFace::NegativeX => -Vec3::unit_x(),
+			CubeFace::PositiveY => Vec3::unit_y(),
+			CubeFace::NegativeY => -Vec3::unit_y(),
+			CubeFace::PositiveZ => Vec3::unit_z(),
+			CubeFace::NegativeZ => -Vec3::unit_z(),
+		}
+	}
+
+	pub fn up_vector(self) -> Vec3<f32> {
+		match self {
+			CubeFace::PositiveY => -Vec3::unit_z(),
+			CubeFace::NegativeY => Vec3::unit_z(),
+			_ => Vec3::unit_y(),
+		}
+	}
+
+	pub fn right_vector(self) -> Vec3<f32> {
+		self.up_vector().cross(self.forward_vector())
+	}
+
+	pub fn direction(self) -> Direction {
+		let (yaw, pitch) = match self {
+			CubeFace::PositiveX => (FRAC_PI_2, 0.),
+			CubeFace::NegativeX => (-FRAC_PI_2, 0.),
+			CubeFace::PositiveY => (0., FRAC_PI_2),
+			CubeFace::NegativeY => (0., -FRAC_PI_2),
+			CubeFace::PositiveZ => (0., 0.),
+			CubeFace::NegativeZ => (PI, 0.),
+		};
+
+		Direction {
+			yaw: Radians::new(yaw),
+			pitch: Radians::new(pitch),
+		}
+	}
+
+	/// The view matrix of the face, with the default conventions.
+	pub fn view_matrix(self, Position(position): Position) -> Mat4<f32> {
+		Mat4::look_at_lh(position, position + self.forward_vector(), self.up_vector())
+	}
+
+	/// The face sampled by a direction, and the texture coordinates on that
+	/// face (from the top-left corner).
+	pub fn sample(direction: Vec3<f32>) -> (CubeFace, Vec2<f32>) {
+		let abs = direction.map(f32::abs);
+
+		// The coordinates going right and down on the face, and the major axis
+		let (face, right, down, major) = if abs.x >= abs.y && abs.x >= abs.z {
+			if direction.x > 0. {
+				(CubeFace::PositiveX, -direction.z, -direction.y, abs.x)
+			} else {
+				(CubeFace::NegativeX, direction.z, -direction.y, abs.x)
+			}
+		} else if abs.y >= abs.z {
+			if direction.y > 0. {
+				(CubeFace::PositiveY, direction.x, direction.z, abs.y)
+			} else {
+				(CubeFace::NegativeY, direction.x, -direction.z, abs.y)
+			}
+		} else if direction.z > 0. {
+			(CubeFace::PositiveZ, direction.x, -direction.y, abs.z)
+		} else {
+			(CubeFace::NegativeZ, -direction.x, -direction.y, abs.z)
+		};
+
+		(face, (Vec2::new(right, down) / major + 1.) / 2.)
+	}
+}
+
+impl CameraConventions {
+	/// The view matrix of a cubemap face for these conventions.
+	///
+	/// Cubemaps are sampled with the same orientation whatever the handedness,
+	/// so for right-handed conventions the face is mirrored to cancel out the
+	/// mirroring of the right-handed projection matrix: the faces can then be
+	/// sampled with world-space directions as-is.
+	pub fn cubemap_view_matrix(self, position: Position, face: CubeFace) -> Mat4<f32> {
+		let view = face.view_matrix(position);
+		match self.handedness {
+			Handedness::Left => view,
+			Handedness::Right => Mat4::<f32>::scaling_3d(Vec3::new(1., 1., -1.)) * view,
+		}
+	}
+}
+
+/// The 90° square frustum of the cubemap faces.
+pub fn calc_cubemap_frustum(z_near: f32, z_far: f32) -> Frustum {
+	Frustum {
+		y_fov: Radians::new(FRAC_PI_2),
+		z_near,
+		z_far,
+	}
+}
+
+/// The views rendering the six faces of a cubemap centered on `position`,
+/// like for point light shadows or reflection probes, in layer order.
+///
+/// The faces must be rendered with a square [`ScreenSize`](crate::ScreenSize).
+pub fn calc_cubemap_views(position: Position, z_near: f32, z_far: f32) -> [CubemapView; 6] {
+	CubeFace::ALL.map(|face| CubemapView {
+		face,
+		frustum: calc_cubemap_frustum(z_near, z_far),
+		direction: face.direction(),
+		view_matrix: face.view_matrix(position),
+	})
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{calc_forward_vector, calc_right_vector, calc_up_vector, ScreenSize};
+
+	const DIRECTIONS: [Vec3<f32>; 8] = [
+		Vec3::new(1., 0.2, -0.3),
+		Vec3::new(-1., -0.7, 0.1),
+		Vec3::new(0.4, 1., 0.9),
+		Vec3::new(-0.5, -1., -0.2),
+		Vec3::new(0.1, 0.6, 1.),
+		Vec3::new(-0.8, 0.3, -1.),
+		Vec3::new(0., 0., 1.),
+		Vec3::new(0.99, -0.99, 0.5),
+	];
+
+	#[test]
+	fn layer_order() {
+		for (i, face) in CubeFace::ALL.into_iter().enumerate() {
+			assert_eq!(face.layer(), i as u32);
+		}
+	}
+
+	#[rstest]
+	#[case(CubeFace::PositiveX, Vec3::new(0., 0., -1.))]
+	#[case(CubeFace::NegativeX, Vec3::new(0., 0., 1.))]
+	#[case(CubeFace::PositiveY, Vec3::new(1., 0., 0.))]
+	#[case(CubeFace::NegativeY, Vec3::new(1., 0., 0.))]
+	#[case(CubeFace::PositiveZ, Vec3::new(1., 0., 0.))]
+	#[case(CubeFace::NegativeZ, Vec3::new(-1., 0., 0.))]
+	fn right_vector(#[case] face: CubeFace, #[case] expected: Vec3<f32>) {
+		assert_eq!(face.right_vector(), expected);
+	}
+
+	#[test]
+	fn direction_matches_vectors() {
+		for face in CubeFace::ALL {
+			let direction = face.direction();
+			assert_relative_eq!(calc_forward_vector(direction), face.forward_vector(), epsilon = 1e-6);
+			assert_relative_eq!(calc_right_vector(direction), face.right_vector(), epsilon = 1e-6);
+			assert_relative_eq!(calc_up_vector(direction), face.up_vector(), epsilon = 1e-6);
+		}
+	}
+
+	#[test]
+	fn sample() {
+		assert_eq!(CubeFace::sample(Vec3::new(1., 0., 0.)), (CubeFace::PositiveX, Vec2::new(0.5, 0.5)));
+		assert_eq!(CubeFace::sample(Vec3::new(1., 1., -1.)).1, Vec2::new(1., 0.));
+		assert_eq!(CubeFace::sample(Vec3::new(0., 1., -1.)).0, CubeFace::PositiveY);
+		assert_eq!(CubeFace::sample(Vec3::new(0., -2., 1.)), (CubeFace::NegativeY, Vec2::new(0.5, 0.25)));
+	}
+
+	/// Rendering a point with a face's matrices must put it where sampling the
+	/// cubemap in its direction reads.
+	#[rstest]
+	#[case(CameraConventions::WGPU)]
+	#[case(CameraConventions { handedness: Handedness::Right, ..CameraConventions::WGPU })]
+	fn rendering_matches_sampling(#[case] conventions: CameraConventions) {
+		let position = Position(Vec3::new(1., -2., 3.));
+		let views = calc_cubemap_views(position, 0.1, 100.);
+
+		for direction in DIRECTIONS {
+			let (face, uv) = CubeFace::sample(direction);
+			let frustum = views[face.layer() as usize].frustum;
+			assert_eq!(views[face.layer() as usize].face, face);
+
+			let projection = conventions.projection_matrix(frustum, ScreenSize::new(256, 256), None);
+			let view = conventions.cubemap_view_matrix(position, face);
+			let clip = projection * view * (*position + direction).with_w(1.);
+			assert!(clip.w > 0.);
+
+			let ndc = clip.xy() / clip.w;
+			assert_relative_eq!(Vec2::new(ndc.x + 1., 1. - ndc.y) / 2., uv, epsilon = 1e-5);
+		}
+	}
+}
@@ -0,0 +1,252 @@
+#![allow(dead_code)]
+
+use vek::{Aabb, Mat4, Sphere, Vec3, Vec4};
+
+use crate::{calc_projection_matrix, calc_view_matrix, Direction, Position, Projection, ScreenSize};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A plane in the form `normal · p + distance = 0`.
+/// Points on the side the normal points to have a positive signed distance.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Plane {
+	pub normal: Vec3<f32>,
+	pub distance: f32,
+}
+
+/// The world-space volume seen by a camera.
+///
+/// The planes are ordered left, right, bottom, top, near, far, and their
+/// normals point inwards. The corners are ordered near then far, each going
+/// bottom-left, bottom-right, top-left, top-right.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ViewFrustum {
+	pub planes: [Plane; 6],
+	pub corners: [Vec3<f32>; 8],
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl Plane {
+	pub fn new(normal: Vec3<f32>, distance: f32) -> Self {
+		Self { normal, distance }
+	}
+
+	/// The plane going through `point`, facing `normal`.
+	pub fn from_point_normal(point: Vec3<f32>, normal: Vec3<f32>) -> Self {
+		let normal = normal.normalized();
+		Self::new(normal, -normal.dot(point))
+	}
+
+	/// Builds a plane from the `(a, b, c, d)` coefficients of `ax + by + cz + d
+	/// = 0`, normalizing them. A degenerate plane (like the far plane of an
+	/// infinite projection) contains everything.
+	pub fn from_coefficients(coefficients: Vec4<f32>) -> Self {
+		let length = coefficients.xyz().magnitude();
+
+		if length <= f32::EPSILON {
+			return Self::new(Vec3::zero(), f32::INFINITY);
+		}
+
+		Self::new(coefficients.xyz() / length, coefficients.w / length)
+	}
+
+	pub fn signed_distance(&self, point: Vec3<f32>) -> f32 {
+		self.normal.dot(point) + self.distance
+	}
+}
+
+impl ViewFrustum {
+	/// The frustum of a camera, built from the same matrices as
+	/// [`calc_view_matrix`] and [`calc_projection_matrix`].
+	pub fn new(position: Position, direction: Direction, projection: impl Into<Projection>, size: ScreenSize) -> Self {
+		Self::from_matrix(calc_projection_matrix(projection, size, None) * calc_view_matrix(position, direction))
+	}
+
+	/// Extracts the frustum from a view-projection matrix with the default
+	/// conventions (zero-to-one depth).
+	///
+	/// The corners of an infinite projection's far plane are not finite.
+	pub fn from_matrix(view_projection: Mat4<f32>) -> Self {
+		let row = |i: usize| view_projection.cols.map(|col| col[i]);
+		let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+		let planes = [w + x, w - x, w + y, w - y, z, w - z].map(Plane::from_coefficients);
+
+		let inverse = view_projection.inverted();
+		let corners = [
+			(-1., -1., 0.),
+			(1., -1., 0.),
+			(-1., 1., 0.),
+			(1., 1., 0.),
+			(-1., -1., 1.),
+			(1., -1., 1.),
+			(-1., 1., 1.),
+			(1., 1., 1.),
+		]
+		.map(|(x, y, z)| {
+			let corner = inverse * Vec4::new(x, y, z, 1.);
+			corner.xyz() / corner.w
+		});
+
+		Self { planes, corners }
+	}
+
+	pub fn contains_point(&self, point: Vec3<f32>) -> bool {
+		self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.)
+	}
+
+	/// Conservative: a sphere close to an edge of the frustum may be reported
+	/// as intersecting although it is just outside.
+	pub fn intersects_sphere(&self, sphere: Sphere<f32, f32>) -> bool {
+		self.planes
+			.iter()
+			.all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+	}
+
+	/// Conservative: a box close to an edge of the frustum may be reported as
+	/// intersecting although it is just outside.
+	pub fn intersects_aabb(&self, aabb: Aabb<f32>) -> bool {
+		self.planes.iter().all(|plane| {
+			// The corner of the box that is the furthest along the normal
+			let corner = Vec3::new(
+				if plane.normal.x >= 0. { aabb.max.x } else { aabb.min.x },
+				if plane.normal.y >= 0. { aabb.max.y } else { aabb.min.y },
+				if plane.normal.z >= 0. { aabb.max.z } else { aabb.min.z },
+			);
+			plane.signed_distance(corner) >= 0.
+		})
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// Returns the indices of the boxes that intersect the frustum.
+pub fn cull_aabbs(frustum: &ViewFrustum, bounds: &[Aabb<f32>]) -> Vec<usize> {
+	(0..bounds.len()).filter(|&i| frustum.intersects_aabb(bounds[i])).collect()
+}
+
+/// Returns the indices of the spheres that intersect the frustum.
+pub fn cull_spheres(frustum: &ViewFrustum, bounds: &[Sphere<f32, f32>]) -> Vec<usize> {
+	(0..bounds.len()).filter(|&i| frustum.intersects_sphere(bounds[i])).collect()
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use std::f32::consts::FRAC_PI_2;
+
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{Degrees, Frustum, Radians};
+
+	fn frustum(yaw: f32) -> ViewFrustum {
+		ViewFrustum::new(
+			Position(Vec3::zero()),
+			Direction {
+				yaw: Degrees::new(yaw).into(),
+				pitch: Degrees::new(0.).into(),
+			},
+			Frustum {
+				y_fov: Radians::new(FRAC_PI_2),
+				z_near: 1.,
+				z_far: 100.,
+			},
+			ScreenSize::new(100, 100),
+		)
+	}
+
+	#[rstest]
+	#[case(0.,  Vec3::new(0., 0., 10.),   true)]
+	#[case(0.,  Vec3::new(9., -9., 10.),  true)]
+	#[case(0.,  Vec3::new(0., 0., -10.),  false)]
+	#[case(0.,  Vec3::new(11., 0., 10.),  false)]
+	#[case(0.,  Vec3::new(0., 0., 0.5),   false)]
+	#[case(0.,  Vec3::new(0., 0., 101.),  false)]
+	#[case(90., Vec3::new(10., 0., 0.),   true)]
+	#[case(90., Vec3::new(0., 0., 10.),   false)]
+	fn frustum_contains_point(#[case] yaw: f32, #[case] point: Vec3<f32>, #[case] expected: bool) {
+		assert_eq!(frustum(yaw).contains_point(point), expected);
+	}
+
+	#[test]
+	fn frustum_corners() {
+		let frustum = frustum(0.);
+		assert_relative_eq!(frustum.corners[0], Vec3::new(-1., -1., 1.), epsilon = 1e-4);
+		assert_relative_eq!(frustum.corners[3], Vec3::new(1., 1., 1.), epsilon = 1e-4);
+		assert_relative_eq!(frustum.corners[7], Vec3::new(100., 100., 100.), epsilon = 1e-2);
+
+		for corner in frustum.corners {
+			for plane in frustum.planes {
+				assert!(plane.signed_distance(corner) >= -1e-3);
+			}
+		}
+	}
+
+	#[rstest]
+	#[case(Vec3::new(12., 0., 10.),  3., true)]
+	#[case(Vec3::new(12., 0., 10.),  1., false)]
+	#[case(Vec3::new(0., 0., -1.),   2.5, true)]
+	#[case(Vec3::new(0., 0., -1.),   1.5, false)]
+	fn frustum_intersects_sphere(#[case] center: Vec3<f32>, #[case] radius: f32, #[case] expected: bool) {
+		assert_eq!(frustum(0.).intersects_sphere(Sphere { center, radius }), expected);
+	}
+
+	#[rstest]
+	#[case(Vec3::new(-1., -1., 5.),  Vec3::new(1., 1., 6.),    true)]
+	#[case(Vec3::new(-50., -1., 5.), Vec3::new(50., 1., 6.),   true)]
+	#[case(Vec3::new(20., -1., 5.),  Vec3::new(30., 1., 6.),   false)]
+	#[case(Vec3::new(-1., -1., -6.), Vec3::new(1., 1., -5.),   false)]
+	#[case(Vec3::new(-1., -1., 99.), Vec3::new(1., 1., 200.),  true)]
+	fn frustum_intersects_aabb(#[case] min: Vec3<f32>, #[case] max: Vec3<f32>, #[case] expected: bool) {
+		assert_eq!(frustum(0.).intersects_aabb(Aabb { min, max }), expected);
+	}
+
+	#[test]
+	fn frustum_batch_culling() {
+		let frustum = frustum(0.);
+		let boxes = [
+			Aabb::new_empty(Vec3::new(0., 0., 10.)),
+			Aabb::new_empty(Vec3::new(0., 0., -10.)),
+			Aabb::new_empty(Vec3::new(5., 5., 50.)),
+		];
+		assert_eq!(cull_aabbs(&frustum, &boxes), vec![0, 2]);
+
+		let spheres = boxes.map(|aabb| Sphere::new(aabb.min, 1.));
+		assert_eq!(cull_spheres(&frustum, &spheres), vec![0, 2]);
+	}
+
+	#[test]
+	fn frustum_infinite_far_plane() {
+		let frustum = ViewFrustum::new(
+			Position(Vec3::zero()),
+			Direction::default(),
+			Projection::InfinitePerspective {
+				y_fov: Radians::new(FRAC_PI_2),
+				z_near: 1.,
+			},
+			ScreenSize::new(100, 100),
+		);
+		assert!(frustum.contains_point(Vec3::new(0., 0., 1e6)));
+		assert!(!frustum.contains_point(Vec3::new(0., 0., 0.5)));
+	}
+}
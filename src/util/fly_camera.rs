@@ -0,0 +1,238 @@
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use vek::{Vec2, Vec3};
+
+use crate::{
+	calc_forward_horizontal_vector, calc_right_vector, Direction, MouseMotionDelta, Position, Radians, Speed,
+	SAFE_FRAC_PI_2,
+};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A first-person "noclip" camera controller.
+///
+/// The movement input is relative to the camera: X is right, Y is up and Z is
+/// forward. Moving forward stays horizontal whatever the pitch, and up is
+/// always the world up.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FlyCameraController {
+	/// The rotation per unit of mouse motion (usually per pixel).
+	pub sensitivity: Radians,
+	pub speed: Speed,
+	/// How fast the camera reaches `speed` and stops again, in units per second
+	/// squared. [`None`] moves at full speed instantly.
+	pub acceleration: Option<f32>,
+	/// The time it takes for the rotation to catch up with ~63% of the mouse
+	/// motion. [`None`] applies the mouse motion instantly.
+	pub smoothing: Option<Duration>,
+	velocity: Vec3<f32>,
+	pending_rotation: Vec2<f32>,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl FlyCameraController {
+	pub fn new(sensitivity: Radians, speed: Speed) -> Self {
+		Self {
+			sensitivity,
+			speed,
+			acceleration: None,
+			smoothing: None,
+			velocity: Vec3::zero(),
+			pending_rotation: Vec2::zero(),
+		}
+	}
+
+	pub fn with_acceleration(mut self, acceleration: f32) -> Self {
+		self.acceleration = Some(acceleration);
+		self
+	}
+
+	pub fn with_smoothing(mut self, smoothing: Duration) -> Self {
+		self.smoothing = Some(smoothing);
+		self
+	}
+
+	/// The current world-space velocity of the camera.
+	pub fn velocity(&self) -> Speed<Vec3<f32>> {
+		Speed::new_per_second(self.velocity)
+	}
+
+	/// Rotates and moves the camera for a frame lasting `delta`.
+	/// The movement input is clamped to a length of 1.
+	pub fn update(
+		&mut self,
+		position: &mut Position,
+		direction: &mut Direction,
+		mouse_motion: MouseMotionDelta,
+		movement: Vec3<f32>,
+		delta: Duration,
+	) {
+		self.rotate(direction, mouse_motion, delta);
+		self.translate(position, *direction, movement, delta);
+	}
+
+	fn rotate(&mut self, direction: &mut Direction, mouse_motion: MouseMotionDelta, delta: Duration) {
+		self.pending_rotation += mouse_motion.as_::<f32>();
+
+		let applied = match self.smoothing {
+			Some(smoothing) if !smoothing.is_zero() => {
+				self.pending_rotation * (1. - (-delta.as_secs_f32() / smoothing.as_secs_f32()).exp())
+			}
+			_ => self.pending_rotation,
+		};
+		self.pending_rotation -= applied;
+
+		// The screen's Y axis points down, so moving the mouse down looks down
+		direction.yaw += self.sensitivity * applied.x;
+		direction.pitch -= self.sensitivity * applied.y;
+		direction
+			.pitch
+			.clamp(Radians::new(-SAFE_FRAC_PI_2), Radians::new(SAFE_FRAC_PI_2));
+	}
+
+	fn translate(&mut self, position: &mut Position, direction: Direction, movement: Vec3<f32>, delta: Duration) {
+		let movement = if movement.magnitude_squared() > 1. {
+			movement.normalized()
+		} else {
+			movement
+		};
+
+		let target = (calc_right_vector(direction) * movement.x
+			+ Vec3::unit_y() * movement.y
+			+ calc_forward_horizontal_vector(direction) * movement.z)
+			* self.speed.per_second();
+
+		self.velocity = match self.acceleration {
+			Some(acceleration) => {
+				let max_change = acceleration * delta.as_secs_f32();
+				let change = target - self.velocity;
+				if change.magnitude() <= max_change {
+					target
+				} else {
+					self.velocity + change.normalized() * max_change
+				}
+			}
+			None => target,
+		};
+
+		position.0 += self.velocity() * delta;
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{AngleDegreesType, AngleRadiansType, Degrees};
+
+	fn controller() -> FlyCameraController {
+		FlyCameraController::new(Degrees::new(0.1).into(), Speed::new_per_second(2.))
+	}
+
+	#[test]
+	fn mouse_rotation() {
+		let (mut position, mut direction) = (Position::default(), Direction::default());
+		let mut controller = controller();
+
+		controller.update(
+			&mut position,
+			&mut direction,
+			MouseMotionDelta::new(100., 50.),
+			Vec3::zero(),
+			Duration::from_millis(16),
+		);
+		assert_relative_eq!(direction.yaw.degrees(), 10., epsilon = 1e-4);
+		assert_relative_eq!(direction.pitch.degrees(), -5., epsilon = 1e-4);
+	}
+
+	#[rstest]
+	#[case(-10_000.)]
+	#[case(10_000.)]
+	fn pitch_is_clamped(#[case] mouse_y: f64) {
+		let (mut position, mut direction) = (Position::default(), Direction::default());
+		let mut controller = controller();
+
+		controller.update(
+			&mut position,
+			&mut direction,
+			MouseMotionDelta::new(0., mouse_y),
+			Vec3::zero(),
+			Duration::from_millis(16),
+		);
+		assert_relative_eq!(direction.pitch.radians().abs(), SAFE_FRAC_PI_2);
+	}
+
+	#[rstest]
+	#[case(0.,  Vec3::new(0., 0., 1.),  Vec3::new(0., 0., 1.))]
+	#[case(90., Vec3::new(0., 0., 1.),  Vec3::new(1., 0., 0.))]
+	#[case(90., Vec3::new(1., 0., 0.),  Vec3::new(0., 0., -1.))]
+	#[case(0.,  Vec3::new(0., 1., 0.),  Vec3::new(0., 1., 0.))]
+	#[case(0.,  Vec3::new(0., 0., 10.), Vec3::new(0., 0., 1.))]
+	fn movement(#[case] yaw: f32, #[case] input: Vec3<f32>, #[case] expected: Vec3<f32>) {
+		let mut position = Position::default();
+		let mut direction = Direction {
+			yaw: Degrees::new(yaw).into(),
+			pitch: Degrees::new(60.).into(),
+		};
+		let mut controller = controller();
+
+		controller.update(
+			&mut position,
+			&mut direction,
+			MouseMotionDelta::zero(),
+			input,
+			Duration::from_millis(500),
+		);
+		assert_relative_eq!(*position, expected, epsilon = 1e-5);
+	}
+
+	#[test]
+	fn acceleration() {
+		let (mut position, mut direction) = (Position::default(), Direction::default());
+		let mut controller = controller().with_acceleration(4.);
+
+		let forward = Vec3::new(0., 0., 1.);
+		let step = Duration::from_millis(250);
+		controller.update(&mut position, &mut direction, MouseMotionDelta::zero(), forward, step);
+		assert_relative_eq!(controller.velocity().per_second().z, 1.);
+		controller.update(&mut position, &mut direction, MouseMotionDelta::zero(), forward, step);
+		assert_relative_eq!(controller.velocity().per_second().z, 2.);
+		controller.update(&mut position, &mut direction, MouseMotionDelta::zero(), forward, step);
+		assert_relative_eq!(controller.velocity().per_second().z, 2.);
+		assert_relative_eq!(position.z, (1. + 2. + 2.) * 0.25);
+	}
+
+	#[test]
+	fn smoothing() {
+		let (mut position, mut direction) = (Position::default(), Direction::default());
+		let mut controller = controller().with_smoothing(Duration::from_millis(100));
+
+		let step = Duration::from_millis(100);
+		controller.update(&mut position, &mut direction, MouseMotionDelta::new(100., 0.), Vec3::zero(), step);
+		assert_relative_eq!(direction.yaw.degrees(), 10. * (1. - (-1f32).exp()), epsilon = 1e-4);
+
+		for _ in 0..50 {
+			controller.update(&mut position, &mut direction, MouseMotionDelta::zero(), Vec3::zero(), step);
+		}
+		assert_relative_eq!(direction.yaw.degrees(), 10., epsilon = 1e-4);
+	}
+}
@@ -1,9 +1,24 @@
 reexport_feature_module!(angle);
+reexport_feature_module!(atlas);
 reexport_feature_module!(camera_3d);
+reexport_feature_module!(camera_path);
+reexport_feature_module!(camera_shake);
+reexport_feature_module!(camera_uniform);
 reexport_feature_module!(convert);
+reexport_feature_module!(cubemap);
+reexport_feature_module!(culling);
+reexport_feature_module!(fly_camera);
+reexport_feature_module!(motion_profile);
+reexport_feature_module!(orbit_camera);
 reexport_feature_module!(path);
+reexport_feature_module!(picking);
+reexport_feature_module!(portal);
 // reexport_feature_module!(shader);
+reexport_feature_module!(shadows);
 reexport_feature_module!(speed);
 reexport_feature_module!(texture);
+reexport_feature_module!(transform);
 reexport_feature_module!(tuples);
+reexport_feature_module!(tween);
 reexport_feature_module!(vec);
+reexport_feature_module!(viewport);
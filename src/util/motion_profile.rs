@@ -0,0 +1,301 @@
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use crate::Speed;
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A time-bounded plan to travel a distance from rest to rest, while staying
+/// within a maximum speed, acceleration and (for S-curves) jerk.
+///
+/// Accelerations are in units per second squared, jerks in units per second
+/// cubed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MotionProfile {
+	direction: f32,
+	segments: Vec<Segment>,
+}
+
+/// The kinematic state of a [`MotionProfile`] at a given time.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MotionState {
+	pub position: f32,
+	pub velocity: Speed,
+	pub acceleration: f32,
+}
+
+/// A phase of constant jerk, starting with the given acceleration.
+/// Position and velocity are integrated continuously across segments.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Segment {
+	duration: f32,
+	acceleration: f32,
+	jerk: f32,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl MotionProfile {
+	/// A trapezoidal velocity profile: constant acceleration up to `max_speed`,
+	/// cruise, then constant deceleration. If the distance is too short to
+	/// reach `max_speed`, the profile becomes triangular.
+	///
+	/// A negative `distance` moves backwards.
+	///
+	/// # Panics
+	///
+	/// Panics if `max_speed` or `max_acceleration` aren't strictly positive.
+	pub fn trapezoidal(distance: f32, max_speed: Speed, max_acceleration: f32) -> Self {
+		let (v_max, a_max) = (max_speed.per_second(), max_acceleration);
+		assert!(v_max > 0., "The max speed of a motion profile must be positive");
+		assert!(a_max > 0., "The max acceleration of a motion profile must be positive");
+
+		let d = distance.abs();
+		let v_peak = v_max.min((d * a_max).sqrt());
+		let t_accel = v_peak / a_max;
+		let t_cruise = if v_peak > 0. { (d - v_peak * t_accel) / v_peak } else { 0. };
+
+		Self::from_segments(
+			distance,
+			vec![
+				Segment::new(t_accel, a_max, 0.),
+				Segment::new(t_cruise, 0., 0.),
+				Segment::new(t_accel, -a_max, 0.),
+			],
+		)
+	}
+
+	/// A jerk-limited "S-curve" profile: the acceleration itself ramps up and
+	/// down at `max_jerk`, which removes the sudden jolts of a trapezoidal
+	/// profile. The peak speed and acceleration are lowered as needed for
+	/// short distances.
+	///
+	/// A negative `distance` moves backwards.
+	///
+	/// # Panics
+	///
+	/// Panics if `max_speed`, `max_acceleration` or `max_jerk` aren't strictly
+	/// positive.
+	pub fn s_curve(distance: f32, max_speed: Speed, max_acceleration: f32, max_jerk: f32) -> Self {
+		let (v_max, a_max, j) = (max_speed.per_second(), max_acceleration, max_jerk);
+		assert!(v_max > 0., "The max speed of a motion profile must be positive");
+		assert!(a_max > 0., "The max acceleration of a motion profile must be positive");
+		assert!(j > 0., "The max jerk of a motion profile must be positive");
+
+		let d = distance.abs();
+
+		// Highest speed reachable without a constant-acceleration phase
+		let v_no_plateau = a_max * a_max / j;
+
+		// Time needed to accelerate from rest to `v`
+		let accel_time = |v: f32| {
+			if v <= v_no_plateau {
+				2. * (v / j).sqrt()
+			} else {
+				v / a_max + a_max / j
+			}
+		};
+
+		// The acceleration phase is symmetric, so its average speed is half the peak
+		// speed, and accelerating then decelerating covers `v * accel_time(v)`
+		let v_peak = if v_max * accel_time(v_max) <= d {
+			v_max
+		} else if v_no_plateau * accel_time(v_no_plateau) >= d {
+			(d * j.sqrt() / 2.).powf(2. / 3.)
+		} else {
+			let b = v_no_plateau;
+			(-b + (b * b + 4. * d * a_max).sqrt()) / 2.
+		};
+
+		let (t_jerk, a_peak) = if v_peak <= v_no_plateau {
+			let t_jerk = (v_peak / j).sqrt();
+			(t_jerk, j * t_jerk)
+		} else {
+			(a_max / j, a_max)
+		};
+		let t_accel = if v_peak > v_no_plateau { v_peak / a_max - t_jerk } else { 0. };
+		let t_cruise = if v_peak > 0. {
+			((d - v_peak * accel_time(v_peak)) / v_peak).max(0.)
+		} else {
+			0.
+		};
+
+		Self::from_segments(
+			distance,
+			vec![
+				Segment::new(t_jerk, 0., j),
+				Segment::new(t_accel, a_peak, 0.),
+				Segment::new(t_jerk, a_peak, -j),
+				Segment::new(t_cruise, 0., 0.),
+				Segment::new(t_jerk, 0., -j),
+				Segment::new(t_accel, -a_peak, 0.),
+				Segment::new(t_jerk, -a_peak, j),
+			],
+		)
+	}
+
+	fn from_segments(distance: f32, segments: Vec<Segment>) -> Self {
+		Self {
+			direction: if distance < 0. { -1. } else { 1. },
+			segments: segments.into_iter().filter(|s| s.duration > 0.).collect(),
+		}
+	}
+
+	/// The time needed to complete the whole motion.
+	pub fn total_time(&self) -> Duration {
+		Duration::from_secs_f32(self.total_secs())
+	}
+
+	fn total_secs(&self) -> f32 {
+		self.segments.iter().map(|s| s.duration).sum()
+	}
+
+	/// The full kinematic state at `time` since the start of the motion.
+	/// Before the start and after the end, the motion is at rest.
+	pub fn sample(&self, time: Duration) -> MotionState {
+		let mut t = time.as_secs_f32();
+		let (mut position, mut velocity) = (0., 0.);
+
+		for segment in &self.segments {
+			let (p, v, a) = segment.integrate(t.min(segment.duration));
+
+			if t < segment.duration {
+				return MotionState {
+					position: self.direction * (position + p + velocity * t),
+					velocity: Speed::new_per_second(self.direction * (velocity + v)),
+					acceleration: self.direction * a,
+				};
+			}
+
+			position += p + velocity * segment.duration;
+			velocity += v;
+			t -= segment.duration;
+		}
+
+		MotionState {
+			position: self.direction * position,
+			velocity: Speed::default(),
+			acceleration: 0.,
+		}
+	}
+
+	pub fn position(&self, time: Duration) -> f32 {
+		self.sample(time).position
+	}
+
+	pub fn velocity(&self, time: Duration) -> Speed {
+		self.sample(time).velocity
+	}
+
+	pub fn acceleration(&self, time: Duration) -> f32 {
+		self.sample(time).acceleration
+	}
+}
+
+impl Segment {
+	fn new(duration: f32, acceleration: f32, jerk: f32) -> Self {
+		Self {
+			duration,
+			acceleration,
+			jerk,
+		}
+	}
+
+	/// The position and velocity gained from rest, and the acceleration, after
+	/// `t` seconds into the segment.
+	fn integrate(&self, t: f32) -> (f32, f32, f32) {
+		let (a, j) = (self.acceleration, self.jerk);
+		(
+			a * t * t / 2. + j * t * t * t / 6.,
+			a * t + j * t * t / 2.,
+			a + j * t,
+		)
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+
+	fn assert_profile_sound(profile: &MotionProfile, distance: f32, v_max: f32, a_max: f32) {
+		let total = profile.total_time();
+		assert_relative_eq!(profile.position(Duration::ZERO), 0.);
+		assert_relative_eq!(profile.position(total), distance, epsilon = 1e-3);
+		assert_relative_eq!(profile.velocity(total).per_second(), 0., epsilon = 1e-3);
+
+		let steps = 200;
+		for i in 0..=steps {
+			let state = profile.sample(total.mul_f32(i as f32 / steps as f32));
+			assert!(state.velocity.per_second().abs() <= v_max + 1e-3);
+			assert!(state.acceleration.abs() <= a_max + 1e-3);
+		}
+	}
+
+	#[rstest]
+	#[case(10., 2., 1.)]
+	#[case(1., 2., 1.)]
+	#[case(-10., 2., 1.)]
+	#[case(0., 2., 1.)]
+	fn trapezoidal(#[case] distance: f32, #[case] v_max: f32, #[case] a_max: f32) {
+		let profile = MotionProfile::trapezoidal(distance, Speed::new_per_second(v_max), a_max);
+		assert_profile_sound(&profile, distance, v_max, a_max);
+	}
+
+	#[test]
+	fn trapezoidal_timing() {
+		// 2s to accelerate (2m), 3s cruising (6m), 2s to decelerate (2m)
+		let profile = MotionProfile::trapezoidal(10., Speed::new_per_second(2.), 1.);
+		assert_relative_eq!(profile.total_time().as_secs_f32(), 7., epsilon = 1e-5);
+		assert_relative_eq!(profile.position(Duration::from_secs(2)), 2., epsilon = 1e-5);
+		assert_relative_eq!(profile.velocity(Duration::from_secs(3)).per_second(), 2.);
+		assert_relative_eq!(profile.acceleration(Duration::from_secs(6)), -1.);
+
+		// Too short to reach the max speed: triangular
+		let profile = MotionProfile::trapezoidal(1., Speed::new_per_second(2.), 1.);
+		assert_relative_eq!(profile.total_time().as_secs_f32(), 2., epsilon = 1e-5);
+		assert_relative_eq!(profile.velocity(Duration::from_secs(1)).per_second(), 1., epsilon = 1e-5);
+	}
+
+	#[rstest]
+	#[case(20., 2., 1., 1.)]
+	#[case(2., 2., 1., 1.)]
+	#[case(0.1, 2., 1., 1.)]
+	#[case(5., 4., 1., 0.5)]
+	#[case(-20., 2., 1., 1.)]
+	fn s_curve(#[case] distance: f32, #[case] v_max: f32, #[case] a_max: f32, #[case] j_max: f32) {
+		let profile = MotionProfile::s_curve(distance, Speed::new_per_second(v_max), a_max, j_max);
+		assert_profile_sound(&profile, distance, v_max, a_max);
+	}
+
+	#[test]
+	fn s_curve_acceleration_is_continuous() {
+		let profile = MotionProfile::s_curve(20., Speed::new_per_second(2.), 1., 1.);
+		let total = profile.total_time();
+
+		let steps = 1000;
+		let dt = total.as_secs_f32() / steps as f32;
+		for i in 0..steps {
+			let a0 = profile.acceleration(Duration::from_secs_f32(i as f32 * dt));
+			let a1 = profile.acceleration(Duration::from_secs_f32((i + 1) as f32 * dt));
+			assert!((a1 - a0).abs() <= dt + 1e-3);
+		}
+	}
+}
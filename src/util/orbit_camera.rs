@@ -0,0 +1,271 @@
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use vek::{Vec2, Vec3};
+
+use crate::{
+	calc_forward_vector, calc_right_vector, calc_up_vector, Angle, Direction, MouseMotionDelta, Position, Radians,
+	SAFE_FRAC_PI_2,
+};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A camera orbiting around a target point, like in model viewers and
+/// strategy games.
+///
+/// Inputs are accumulated by [`rotate`](Self::rotate), [`pan`](Self::pan) and
+/// [`zoom`](Self::zoom), and applied by [`update`](Self::update), which
+/// smoothes them out over time when `damping` is set.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OrbitCamera {
+	pub target: Vec3<f32>,
+	pub distance: f32,
+	pub yaw: Angle,
+	pub pitch: Angle,
+	pub min_distance: f32,
+	pub max_distance: f32,
+	/// The rotation per unit of mouse motion (usually per pixel).
+	pub rotate_sensitivity: Radians,
+	/// The panning per unit of mouse motion, relative to the distance to the
+	/// target so that the target follows the cursor at any zoom.
+	pub pan_sensitivity: f32,
+	/// The zoom factor per unit of zoom input, usually a scroll wheel step.
+	pub zoom_sensitivity: f32,
+	/// The time it takes for the camera to catch up with ~63% of the inputs.
+	/// [`None`] applies the inputs instantly.
+	pub damping: Option<Duration>,
+	pending_rotation: Vec2<f32>,
+	pending_pan: Vec3<f32>,
+	pending_zoom: f32,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl OrbitCamera {
+	pub fn new(target: Vec3<f32>, distance: f32, yaw: Angle, pitch: Angle) -> Self {
+		Self {
+			target,
+			distance,
+			yaw,
+			pitch,
+			min_distance: 0.1,
+			max_distance: f32::INFINITY,
+			rotate_sensitivity: Radians::new(0.005),
+			pan_sensitivity: 0.001,
+			zoom_sensitivity: 0.1,
+			damping: None,
+			pending_rotation: Vec2::zero(),
+			pending_pan: Vec3::zero(),
+			pending_zoom: 0.,
+		}
+	}
+
+	/// # Panics
+	///
+	/// If `min_distance` is greater than `max_distance`, or either is NaN.
+	pub fn with_zoom_limits(mut self, min_distance: f32, max_distance: f32) -> Self {
+		assert!(
+			min_distance <= max_distance,
+			"invalid zoom limits: the minimum distance {min_distance} must not exceed the maximum {max_distance}"
+		);
+		self.min_distance = min_distance;
+		self.max_distance = max_distance;
+		self.distance = self.distance.clamp(min_distance, max_distance);
+		self
+	}
+
+	pub fn with_damping(mut self, damping: Duration) -> Self {
+		self.damping = Some(damping);
+		self
+	}
+
+	/// The direction the camera is looking in, towards the target.
+	pub fn direction(&self) -> Direction {
+		Direction {
+			yaw: self.yaw,
+			pitch: self.pitch,
+		}
+	}
+
+	pub fn position(&self) -> Position {
+		Position(self.target - calc_forward_vector(self.direction()) * self.distance)
+	}
+
+	/// Orbits around the target. Moving the mouse right turns the camera right,
+	/// moving it down makes the camera look down.
+	pub fn rotate(&mut self, mouse_motion: MouseMotionDelta) {
+		self.pending_rotation += mouse_motion.as_::<f32>();
+	}
+
+	/// Moves the target in the view plane, dragging the scene along with the
+	/// mouse.
+	pub fn pan(&mut self, mouse_motion: MouseMotionDelta) {
+		let mouse_motion = mouse_motion.as_::<f32>() * self.pan_sensitivity * self.distance;
+		let direction = self.direction();
+		self.pending_pan += -calc_right_vector(direction) * mouse_motion.x + calc_up_vector(direction) * mouse_motion.y;
+	}
+
+	/// Zooms in for positive amounts and out for negative ones.
+	pub fn zoom(&mut self, amount: f32) {
+		self.pending_zoom += amount;
+	}
+
+	/// Stops any movement still being applied by the damping.
+	pub fn stop(&mut self) {
+		self.pending_rotation = Vec2::zero();
+		self.pending_pan = Vec3::zero();
+		self.pending_zoom = 0.;
+	}
+
+	/// Applies the pending inputs for a frame lasting `delta`.
+	pub fn update(&mut self, delta: Duration) {
+		let fraction = match self.damping {
+			Some(damping) if !damping.is_zero() => 1. - (-delta.as_secs_f32() / damping.as_secs_f32()).exp(),
+			_ => 1.,
+		};
+
+		let rotation = self.pending_rotation * fraction;
+		let pan = self.pending_pan * fraction;
+		let zoom = self.pending_zoom * fraction;
+		self.pending_rotation -= rotation;
+		self.pending_pan -= pan;
+		self.pending_zoom -= zoom;
+
+		self.yaw += self.rotate_sensitivity * rotation.x;
+		self.pitch -= self.rotate_sensitivity * rotation.y;
+		self.pitch.clamp(Radians::new(-SAFE_FRAC_PI_2), Radians::new(SAFE_FRAC_PI_2));
+
+		self.target += pan;
+
+		// Unlike `clamp`, doesn't panic on limits set wrongly through the fields
+		self.distance *= (-zoom * self.zoom_sensitivity).exp();
+		self.distance = self.distance.max(self.min_distance).min(self.max_distance);
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{calc_view_matrix, AngleDegreesType, AngleRadiansType, Degrees};
+
+	fn camera(yaw: f32, pitch: f32) -> OrbitCamera {
+		OrbitCamera::new(
+			Vec3::new(1., 2., 3.),
+			5.,
+			Degrees::new(yaw).into(),
+			Degrees::new(pitch).into(),
+		)
+	}
+
+	#[rstest]
+	#[case(0.,   0.)]
+	#[case(90.,  30.)]
+	#[case(-45., -60.)]
+	fn looks_at_target(#[case] yaw: f32, #[case] pitch: f32) {
+		let camera = camera(yaw, pitch);
+		let view = calc_view_matrix(camera.position(), camera.direction());
+		assert_relative_eq!(view.mul_point(camera.target), Vec3::new(0., 0., 5.), epsilon = 1e-4);
+	}
+
+	#[test]
+	fn position() {
+		assert_relative_eq!(*camera(0., 0.).position(), Vec3::new(1., 2., -2.), epsilon = 1e-5);
+		assert_relative_eq!(*camera(90., 0.).position(), Vec3::new(-4., 2., 3.), epsilon = 1e-5);
+		assert_relative_eq!(*camera(0., -90.).position(), Vec3::new(1., 7., 3.), epsilon = 1e-3);
+	}
+
+	#[test]
+	fn rotate() {
+		let mut camera = camera(0., 0.);
+		camera.rotate(MouseMotionDelta::new(100., -1e6));
+		camera.update(Duration::from_millis(16));
+		assert_relative_eq!(camera.yaw.degrees(), 0.5f32.to_degrees(), epsilon = 1e-4);
+		assert_relative_eq!(camera.pitch.radians(), SAFE_FRAC_PI_2);
+	}
+
+	#[test]
+	fn zoom_limits() {
+		let mut camera = camera(0., 0.).with_zoom_limits(2., 10.);
+		camera.zoom(1.);
+		camera.update(Duration::from_millis(16));
+		assert_relative_eq!(camera.distance, 5. * (-0.1f32).exp());
+
+		camera.zoom(100.);
+		camera.update(Duration::from_millis(16));
+		assert_relative_eq!(camera.distance, 2.);
+
+		camera.zoom(-100.);
+		camera.update(Duration::from_millis(16));
+		assert_relative_eq!(camera.distance, 10.);
+	}
+
+	#[test]
+	#[should_panic(expected = "invalid zoom limits")]
+	fn zoom_limits_inverted() {
+		camera(0., 0.).with_zoom_limits(10., 2.);
+	}
+
+	#[test]
+	#[should_panic(expected = "invalid zoom limits")]
+	fn zoom_limits_nan() {
+		camera(0., 0.).with_zoom_limits(f32::NAN, 2.);
+	}
+
+	#[test]
+	fn zoom_limits_fields() {
+		// Set wrongly through the fields, the update still doesn't panic
+		let mut camera = camera(0., 0.);
+		(camera.min_distance, camera.max_distance) = (10., 2.);
+		camera.zoom(1.);
+		camera.update(Duration::from_millis(16));
+		assert_eq!(camera.distance, 2.);
+	}
+
+	#[test]
+	fn pan() {
+		let mut camera = camera(90., 0.);
+		camera.pan(MouseMotionDelta::new(200., 100.));
+		camera.update(Duration::from_millis(16));
+
+		// Looking towards +X, right is -Z: dragging right moves the target to +Z
+		assert_relative_eq!(camera.target, Vec3::new(1., 2.5, 4.), epsilon = 1e-5);
+		assert_relative_eq!(camera.distance, 5.);
+	}
+
+	#[test]
+	fn damping() {
+		let mut camera = camera(0., 0.).with_damping(Duration::from_millis(100));
+		camera.rotate(MouseMotionDelta::new(100., 0.));
+
+		camera.update(Duration::from_millis(100));
+		assert_relative_eq!(camera.yaw.degrees(), 0.5f32.to_degrees() * (1. - (-1f32).exp()), epsilon = 1e-4);
+
+		for _ in 0..50 {
+			camera.update(Duration::from_millis(100));
+		}
+		assert_relative_eq!(camera.yaw.degrees(), 0.5f32.to_degrees(), epsilon = 1e-4);
+
+		camera.rotate(MouseMotionDelta::new(100., 0.));
+		camera.stop();
+		camera.update(Duration::from_millis(100));
+		assert_relative_eq!(camera.yaw.degrees(), 0.5f32.to_degrees(), epsilon = 1e-4);
+	}
+}
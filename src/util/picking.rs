@@ -0,0 +1,172 @@
+#![allow(dead_code)]
+
+use vek::{Extent2, Mat4, Ray, Vec2, Vec3, Vec4};
+
+use crate::{calc_projection_matrix, calc_view_matrix, Direction, Position, Projection, ScreenSize};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+// Screen coordinates are in pixels, with the origin in the top-left corner and
+// Y pointing down, like the cursor positions reported by winit.
+
+/// Converts a cursor position on the screen to normalized device coordinates.
+pub fn screen_to_ndc(cursor: Vec2<f32>, Extent2 { w, h }: ScreenSize) -> Vec2<f32> {
+	Vec2::new(cursor.x / w as f32 * 2. - 1., 1. - cursor.y / h as f32 * 2.)
+}
+
+/// Converts normalized device coordinates to a position on the screen.
+pub fn ndc_to_screen(ndc: Vec2<f32>, Extent2 { w, h }: ScreenSize) -> Vec2<f32> {
+	Vec2::new((ndc.x + 1.) / 2. * w as f32, (1. - ndc.y) / 2. * h as f32)
+}
+
+/// The world-space ray going through the cursor, starting on the near plane.
+/// The direction is normalized.
+pub fn screen_to_world_ray(
+	cursor: Vec2<f32>,
+	size: ScreenSize,
+	position: Position,
+	direction: Direction,
+	projection: impl Into<Projection>,
+) -> Ray<f32> {
+	let view_projection = calc_projection_matrix(projection, size, None) * calc_view_matrix(position, direction);
+	ndc_to_world_ray(screen_to_ndc(cursor, size), view_projection)
+}
+
+/// The world-space ray going through a point in normalized device coordinates,
+/// for a view-projection matrix with the default conventions.
+pub fn ndc_to_world_ray(ndc: Vec2<f32>, view_projection: Mat4<f32>) -> Ray<f32> {
+	let inverse = view_projection.inverted();
+	let unproject = |depth: f32| {
+		let point = inverse * Vec4::new(ndc.x, ndc.y, depth, 1.);
+		point.xyz() / point.w
+	};
+
+	// The middle of the depth range rather than the far plane, which is at
+	// infinity for infinite projections
+	let (near, middle) = (unproject(0.), unproject(0.5));
+	Ray::new(near, (middle - near).normalized())
+}
+
+/// Projects a world-space point to the screen.
+/// The returned Z is the depth of the point, from 0 on the near plane to 1 on
+/// the far plane.
+///
+/// Returns [`None`] if the point is behind the camera. Points outside of the
+/// screen or the depth range are still returned.
+pub fn world_to_screen(
+	point: Vec3<f32>,
+	size: ScreenSize,
+	position: Position,
+	direction: Direction,
+	projection: impl Into<Projection>,
+) -> Option<Vec3<f32>> {
+	let view = calc_view_matrix(position, direction) * point.with_w(1.);
+	let clip = calc_projection_matrix(projection, size, None) * view;
+
+	// W is always 1 for orthographic projections, only the view-space depth
+	// tells whether the point is behind
+	if view.z < 0. || clip.w <= 0. {
+		return None;
+	}
+
+	let ndc = clip.xyz() / clip.w;
+	Some(ndc_to_screen(ndc.xy(), size).with_z(ndc.z))
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use std::f32::consts::FRAC_PI_2;
+
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{calc_forward_vector, Degrees, Frustum, Radians};
+
+	const SIZE: ScreenSize = ScreenSize::new(800, 600);
+	const FRUSTUM: Frustum = Frustum {
+		y_fov: Radians::new(FRAC_PI_2),
+		z_near: 0.1,
+		z_far: 100.,
+	};
+
+	fn direction(yaw: f32, pitch: f32) -> Direction {
+		Direction {
+			yaw: Degrees::new(yaw).into(),
+			pitch: Degrees::new(pitch).into(),
+		}
+	}
+
+	#[rstest]
+	#[case(0.,   0.)]
+	#[case(90.,  0.)]
+	#[case(30.,  45.)]
+	#[case(200., -60.)]
+	fn ray_through_center_is_forward(#[case] yaw: f32, #[case] pitch: f32) {
+		let position = Position(Vec3::new(1., 2., 3.));
+		let ray = screen_to_world_ray(Vec2::new(400., 300.), SIZE, position, direction(yaw, pitch), FRUSTUM);
+		assert_relative_eq!(ray.direction, calc_forward_vector(direction(yaw, pitch)), epsilon = 1e-4);
+		assert_relative_eq!(ray.origin, *position + ray.direction * 0.1, epsilon = 1e-4);
+	}
+
+	#[test]
+	fn ray_through_corner() {
+		// With a 90° vertical fov, the top edge of the screen is at 45° upwards
+		let ray = screen_to_world_ray(Vec2::new(400., 0.), SIZE, Position::default(), direction(0., 0.), FRUSTUM);
+		assert_relative_eq!(ray.direction, Vec3::new(0., 1., 1.).normalized(), epsilon = 1e-4);
+
+		let ray = screen_to_world_ray(Vec2::new(800., 300.), SIZE, Position::default(), direction(0., 0.), FRUSTUM);
+		assert_relative_eq!(ray.direction, Vec3::new(4. / 3., 0., 1.).normalized(), epsilon = 1e-4);
+	}
+
+	#[rstest]
+	#[case(Vec2::new(0., 0.))]
+	#[case(Vec2::new(123., 456.))]
+	#[case(Vec2::new(800., 600.))]
+	fn roundtrip(#[case] cursor: Vec2<f32>) {
+		let (position, direction) = (Position(Vec3::new(-5., 1., 2.)), direction(45., 10.));
+		let ray = screen_to_world_ray(cursor, SIZE, position, direction, FRUSTUM);
+
+		let point = ray.origin + ray.direction * 10.;
+		let screen = world_to_screen(point, SIZE, position, direction, FRUSTUM).unwrap();
+		assert_relative_eq!(screen.xy(), cursor, epsilon = 1e-2);
+		assert!(screen.z > 0. && screen.z < 1.);
+	}
+
+	#[test]
+	fn world_to_screen_behind() {
+		let screen = world_to_screen(Vec3::new(0., 0., -5.), SIZE, Position::default(), direction(0., 0.), FRUSTUM);
+		assert_eq!(screen, None);
+	}
+
+	#[test]
+	fn world_to_screen_behind_orthographic() {
+		let projection = Projection::Orthographic {
+			height: 10.,
+			z_near: -10.,
+			z_far: 10.,
+		};
+		let behind = world_to_screen(Vec3::new(0., 0., -5.), SIZE, Position::default(), direction(0., 0.), projection);
+		let front = world_to_screen(Vec3::new(0., 0., 5.), SIZE, Position::default(), direction(0., 0.), projection);
+		assert_eq!(behind, None);
+		assert_relative_eq!(front.unwrap(), Vec3::new(400., 300., 0.75), epsilon = 1e-3);
+	}
+
+	#[test]
+	fn world_to_screen_depth() {
+		let near = world_to_screen(Vec3::new(0., 0., 0.1), SIZE, Position::default(), direction(0., 0.), FRUSTUM);
+		let far = world_to_screen(Vec3::new(0., 0., 100.), SIZE, Position::default(), direction(0., 0.), FRUSTUM);
+		assert_relative_eq!(near.unwrap(), Vec3::new(400., 300., 0.), epsilon = 1e-3);
+		assert_relative_eq!(far.unwrap(), Vec3::new(400., 300., 1.), epsilon = 1e-3);
+	}
+}
@@ -0,0 +1,259 @@
+#![allow(dead_code)]
+
+use std::f32::consts::PI;
+
+use vek::{Mat4, Vec3, Vec4};
+
+use crate::{calc_forward_vector, Direction, Plane, Position};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// One side of a portal: a flat window facing `direction`, centered on
+/// `position`.
+///
+/// Looking into the front of a portal shows the view out of the front of its
+/// destination.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Portal {
+	pub position: Position,
+	pub direction: Direction,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// Replaces the near plane of a projection matrix with an arbitrary clip plane,
+/// using Eric Lengyel's oblique view frustum. The far plane is tilted to still
+/// contain the view, which costs some depth precision.
+///
+/// This keeps what is behind a mirror or a portal from being rendered into it,
+/// without a clip distance in the shaders.
+///
+/// The `clip_plane` is in world space, and its normal points towards the part
+/// of the scene that is kept. The camera has to be behind the plane, otherwise
+/// nothing would be visible and `projection` is returned unchanged.
+///
+/// Works with projections using the default conventions (zero-to-one depth).
+pub fn calc_oblique_projection_matrix(projection: Mat4<f32>, view: Mat4<f32>, clip_plane: Plane) -> Mat4<f32> {
+	// Planes are transformed by the inverse transpose
+	let plane = view.inverted().transposed() * clip_plane.normal.with_w(clip_plane.distance);
+
+	if plane.w >= 0. {
+		return projection;
+	}
+
+	// The corner of the view frustum opposite to the clip plane, which the new
+	// far plane must go through
+	let corner = projection.inverted() * Vec4::new(plane.x.signum(), plane.y.signum(), 1., 1.);
+	let plane = plane / plane.dot(corner);
+
+	let mut matrix = projection;
+	for col in 0..4 {
+		matrix[(2, col)] = plane[col];
+	}
+	matrix
+}
+
+/// Mirrors a point across a plane.
+pub fn reflect_position(Position(position): Position, plane: Plane) -> Position {
+	Position(position - plane.normal * 2. * plane.signed_distance(position))
+}
+
+/// Mirrors a direction across a plane.
+///
+/// A mirrored camera is upside-down, which [`Direction`] can't represent: the
+/// image rendered with the reflected position and direction is flipped
+/// vertically compared to the real reflection, so its texture has to be
+/// sampled with Y flipped. Use [`calc_reflection_matrix`] for an exact
+/// reflection instead.
+pub fn reflect_direction(direction: Direction, plane: Plane) -> Direction {
+	let forward = calc_forward_vector(direction);
+	Direction::from_forward_or_yaw(forward - plane.normal * 2. * plane.normal.dot(forward), direction.yaw)
+}
+
+/// The world-space matrix mirroring everything across a plane. Rendering with
+/// `view * reflection` shows the exact mirror image, but inverts the winding of
+/// the triangles, so the front face has to be switched.
+#[rustfmt::skip]
+pub fn calc_reflection_matrix(Plane { normal: n, distance: d }: Plane) -> Mat4<f32> {
+	Mat4::new(
+		1. - 2. * n.x * n.x,     -2. * n.x * n.y,     -2. * n.x * n.z, -2. * n.x * d,
+		    -2. * n.y * n.x, 1. - 2. * n.y * n.y,     -2. * n.y * n.z, -2. * n.y * d,
+		    -2. * n.z * n.x,     -2. * n.z * n.y, 1. - 2. * n.z * n.z, -2. * n.z * d,
+		                 0.,                  0.,                  0.,            1.,
+	)
+}
+
+impl Portal {
+	pub fn new(position: Position, direction: Direction) -> Self {
+		Self { position, direction }
+	}
+
+	/// The plane of the portal, facing out of its front.
+	pub fn plane(&self) -> Plane {
+		Plane::from_point_normal(*self.position, calc_forward_vector(self.direction))
+	}
+
+	/// The transform from the portal's local space (+Z out of the front) to the
+	/// world.
+	pub fn to_world(&self) -> Mat4<f32> {
+		Mat4::<f32>::translation_3d(*self.position) * Mat4::from(self.direction.to_quaternion())
+	}
+
+	/// The transform taking what is in front of this portal to what is seen out
+	/// of the front of `destination`.
+	pub fn calc_transform(&self, destination: &Portal) -> Mat4<f32> {
+		destination.to_world() * Mat4::rotation_y(PI) * self.to_world().inverted()
+	}
+
+	/// The virtual camera seeing the destination's side of a camera looking
+	/// into this portal.
+	///
+	/// The virtual camera is behind the destination, so it should be rendered
+	/// with [`calc_oblique_projection_matrix`] and the destination's
+	/// [`plane`](Self::plane) to clip what is behind the destination.
+	pub fn calc_virtual_camera(
+		&self,
+		destination: &Portal,
+		Position(position): Position,
+		direction: Direction,
+	) -> (Position, Direction) {
+		let transform = self.calc_transform(destination);
+		let forward = transform.mul_direction(calc_forward_vector(direction));
+
+		(
+			Position(transform.mul_point(position)),
+			Direction::from_forward_or_yaw(forward, direction.yaw),
+		)
+	}
+
+	/// The exact view matrix of the virtual camera, which also handles portals
+	/// that aren't upright.
+	pub fn calc_virtual_view_matrix(&self, destination: &Portal, view: Mat4<f32>) -> Mat4<f32> {
+		view * self.calc_transform(destination).inverted()
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{
+		calc_projection_matrix, calc_view_matrix, AngleDegreesType, Degrees, Frustum, Radians, ScreenSize,
+	};
+
+	const FRUSTUM: Frustum = Frustum {
+		y_fov: Radians::new(1.2),
+		z_near: 0.1,
+		z_far: 100.,
+	};
+
+	fn direction(yaw: f32, pitch: f32) -> Direction {
+		Direction {
+			yaw: Degrees::new(yaw).into(),
+			pitch: Degrees::new(pitch).into(),
+		}
+	}
+
+	fn depth(matrix: Mat4<f32>, point: Vec3<f32>) -> f32 {
+		let clip = matrix * point.with_w(1.);
+		clip.z / clip.w
+	}
+
+	#[test]
+	fn oblique_near_plane() {
+		let (position, direction) = (Position(Vec3::new(0., 2., 0.)), direction(0., -20.));
+		let view = calc_view_matrix(position, direction);
+		let projection = calc_projection_matrix(FRUSTUM, ScreenSize::new(800, 600), None);
+
+		// Water at Y = 0, keeping what is below
+		let water = Plane::new(-Vec3::unit_y(), 0.);
+		let oblique = calc_oblique_projection_matrix(projection, view, water) * view;
+
+		// On the plane: exactly on the near plane. Above: clipped. Below: visible
+		assert_relative_eq!(depth(oblique, Vec3::new(0.5, 0., 10.)), 0., epsilon = 1e-4);
+		assert!(depth(oblique, Vec3::new(0., 0.5, 10.)) < 0.);
+		let below = depth(oblique, Vec3::new(0., -1., 10.));
+		assert!(below > 0. && below < 1.);
+
+		// The whole original view is still within the far plane
+		let far = *position + calc_forward_vector(direction) * 99.;
+		assert!(depth(oblique, far) <= 1. + 1e-4);
+	}
+
+	#[test]
+	fn oblique_camera_in_front() {
+		let view = calc_view_matrix(Position(Vec3::new(0., 2., 0.)), direction(0., 0.));
+		let projection = calc_projection_matrix(FRUSTUM, ScreenSize::new(800, 600), None);
+		let plane = Plane::new(Vec3::unit_y(), 0.);
+		assert_eq!(calc_oblique_projection_matrix(projection, view, plane), projection);
+	}
+
+	#[rstest]
+	#[case(Vec3::new(1., 2., 3.),  Vec3::new(1., -2., 3.))]
+	#[case(Vec3::new(0., -5., 0.), Vec3::new(0., 5., 0.))]
+	fn reflect_across_water(#[case] point: Vec3<f32>, #[case] expected: Vec3<f32>) {
+		let water = Plane::new(Vec3::unit_y(), 0.);
+		assert_relative_eq!(*reflect_position(Position(point), water), expected);
+		assert_relative_eq!(calc_reflection_matrix(water).mul_point(point), expected);
+	}
+
+	#[test]
+	fn reflect_across_wall() {
+		// A wall at X = 2, facing -X
+		let wall = Plane::from_point_normal(Vec3::new(2., 0., 0.), -Vec3::unit_x());
+		assert_relative_eq!(*reflect_position(Position(Vec3::new(0., 1., 5.)), wall), Vec3::new(4., 1., 5.));
+
+		let reflected = reflect_direction(direction(30., 10.), wall);
+		assert_relative_eq!(reflected.yaw.degrees(), -30., epsilon = 1e-3);
+		assert_relative_eq!(reflected.pitch.degrees(), 10., epsilon = 1e-3);
+
+		let water = Plane::new(Vec3::unit_y(), 0.);
+		let reflected = reflect_direction(direction(30., -25.), water);
+		assert_relative_eq!(reflected.yaw.degrees(), 30., epsilon = 1e-3);
+		assert_relative_eq!(reflected.pitch.degrees(), 25., epsilon = 1e-3);
+	}
+
+	#[test]
+	fn portal_virtual_camera() {
+		// Entering a portal facing -Z at the origin, exiting a portal facing +X
+		let source = Portal::new(Position(Vec3::zero()), direction(180., 0.));
+		let destination = Portal::new(Position(Vec3::new(10., 0., 10.)), direction(90., 0.));
+
+		// 2 units in front of the source and half a unit to the right, looking
+		// into it
+		let (camera_position, camera_direction) = (Position(Vec3::new(0.5, 1., -2.)), direction(0., 5.));
+		let (position, virtual_direction) = source.calc_virtual_camera(&destination, camera_position, camera_direction);
+
+		// 2 units behind the destination and still to the right, looking out of it
+		assert_relative_eq!(*position, Vec3::new(8., 1., 9.5), epsilon = 1e-5);
+		assert_relative_eq!(virtual_direction.yaw.degrees(), 90., epsilon = 1e-3);
+		assert_relative_eq!(virtual_direction.pitch.degrees(), 5., epsilon = 1e-3);
+
+		let view = calc_view_matrix(camera_position, camera_direction);
+		assert_relative_eq!(
+			source.calc_virtual_view_matrix(&destination, view),
+			calc_view_matrix(position, virtual_direction),
+			epsilon = 1e-4
+		);
+
+		// The destination's plane clips what is behind it, where the camera is
+		assert!(destination.plane().signed_distance(*position) < 0.);
+		assert!(destination.plane().signed_distance(Vec3::new(15., 0., 10.)) > 0.);
+	}
+}
@@ -0,0 +1,322 @@
+#![allow(dead_code)]
+
+#[cfg(feature = "texture")]
+use vek::Extent2;
+use vek::{FrustumPlanes, Mat4, Vec3, Vec4};
+
+use crate::{calc_projection_matrix, calc_view_matrix, Direction, Frustum, Position, ScreenSize};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// How the view frustum is divided between the cascades.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CascadeSplit {
+	/// Equal depth ranges. Wastes resolution far away.
+	Uniform,
+	/// Depth ranges growing geometrically, matching the perspective. Gives tiny
+	/// first cascades when the near plane is close.
+	Logarithmic,
+	/// Blends the uniform (0) and logarithmic (1) splits, as described in
+	/// "Parallel-Split Shadow Maps" (Zhang et al.). 0.5 to 0.9 usually works
+	/// well.
+	Practical(f32),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CascadeSettings {
+	pub count: usize,
+	pub split: CascadeSplit,
+	/// The width and height of each shadow map, in texels.
+	pub resolution: u32,
+	/// How far behind a cascade the light is moved back, so that shadow casters
+	/// outside of the view (like a mountain behind the camera) are still
+	/// rendered into the shadow map.
+	pub caster_distance: f32,
+}
+
+/// The shadow map of a slice of the view frustum.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadowCascade {
+	/// The view-space depth where the cascade starts.
+	pub near: f32,
+	/// The view-space depth where the cascade ends.
+	pub far: f32,
+	/// The orthographic view-projection of the light, with the default
+	/// conventions (zero-to-one depth).
+	pub view_projection: Mat4<f32>,
+	/// The size of a shadow map texel in world units, useful to scale the depth
+	/// bias.
+	pub texel_size: f32,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl CascadeSplit {
+	/// The view-space depth where each of the `count` cascades ends, the last
+	/// one being `z_far`.
+	pub fn distances(self, z_near: f32, z_far: f32, count: usize) -> Vec<f32> {
+		(1..=count)
+			.map(|i| {
+				let fraction = i as f32 / count as f32;
+				let uniform = z_near + (z_far - z_near) * fraction;
+				let logarithmic = z_near * (z_far / z_near).powf(fraction);
+
+				match self {
+					CascadeSplit::Uniform => uniform,
+					CascadeSplit::Logarithmic => logarithmic,
+					CascadeSplit::Practical(lambda) => lambda * logarithmic + (1. - lambda) * uniform,
+				}
+			})
+			.collect()
+	}
+}
+
+/// Computes the shadow cascades of a camera for a directional light.
+///
+/// `light_direction` is the direction the light travels in, from the light
+/// towards the scene.
+///
+/// Each cascade is fitted around the bounding sphere of its slice of the view
+/// frustum, so its size doesn't change when the camera rotates, and snapped to
+/// the shadow map texels, so that the shadows don't shimmer when the camera
+/// moves.
+pub fn calc_shadow_cascades(
+	position: Position,
+	direction: Direction,
+	frustum: Frustum,
+	size: ScreenSize,
+	light_direction: Vec3<f32>,
+	settings: CascadeSettings,
+) -> Vec<ShadowCascade> {
+	let light_direction = light_direction.normalized();
+	let view = calc_view_matrix(position, direction);
+
+	let mut near = frustum.z_near;
+	let distances = settings.split.distances(frustum.z_near, frustum.z_far, settings.count);
+
+	distances
+		.into_iter()
+		.map(|far| {
+			let slice = Frustum {
+				z_near: near,
+				z_far: far,
+				..frustum
+			};
+			let corners = calc_frustum_corners(calc_projection_matrix(slice, size, None) * view);
+			let cascade = calc_cascade(near, far, corners, light_direction, settings);
+			near = far;
+			cascade
+		})
+		.collect()
+}
+
+/// The world-space corners of the volume seen through a view-projection
+/// matrix.
+fn calc_frustum_corners(view_projection: Mat4<f32>) -> [Vec3<f32>; 8] {
+	let inverse = view_projection.inverted();
+	let mut corners = [Vec3::zero(); 8];
+
+	for (i, corner) in corners.iter_mut().enumerate() {
+		let ndc = Vec4::new(
+			if i & 1 == 0 { -1. } else { 1. },
+			if i & 2 == 0 { -1. } else { 1. },
+			if i & 4 == 0 { 0. } else { 1. },
+			1.,
+		);
+		let point = inverse * ndc;
+		*corner = point.xyz() / point.w;
+	}
+
+	corners
+}
+
+fn calc_cascade(
+	near: f32,
+	far: f32,
+	corners: [Vec3<f32>; 8],
+	light_direction: Vec3<f32>,
+	settings: CascadeSettings,
+) -> ShadowCascade {
+	let center = corners.into_iter().sum::<Vec3<f32>>() / 8.;
+	let radius = corners.iter().map(|corner| corner.distance(center)).fold(0., f32::max);
+	// Rounding up avoids tiny radius changes from floating point errors, which
+	// would change the texel size
+	let radius = (radius * 16.).ceil() / 16.;
+
+	let up = if light_direction.y.abs() > 0.99 {
+		Vec3::unit_z()
+	} else {
+		Vec3::unit_y()
+	};
+	let eye = center - light_direction * (radius + settings.caster_distance);
+	let light_view = Mat4::look_at_lh(eye, center, up);
+	let light_projection = Mat4::orthographic_lh_zo(FrustumPlanes {
+		left: -radius,
+		right: radius,
+		bottom: -radius,
+		top: radius,
+		near: 0.,
+		far: 2. * radius + settings.caster_distance,
+	});
+
+	let view_projection = snap_to_texels(light_projection * light_view, settings.resolution);
+
+	ShadowCascade {
+		near,
+		far,
+		view_projection,
+		texel_size: 2. * radius / settings.resolution as f32,
+	}
+}
+
+/// Translates the projection so that the world origin falls exactly on a
+/// texel, so that the scene is always rasterized at the same sub-texel
+/// positions.
+fn snap_to_texels(mut view_projection: Mat4<f32>, resolution: u32) -> Mat4<f32> {
+	let texels_per_unit = resolution as f32 / 2.;
+	let origin = (view_projection * Vec4::unit_w()).xy() * texels_per_unit;
+	let offset = (origin.round() - origin) / texels_per_unit;
+
+	// Orthographic, so W is always 1 and the translation is in NDC
+	view_projection[(0, 3)] += offset.x;
+	view_projection[(1, 3)] += offset.y;
+	view_projection
+}
+
+/// Creates one depth texture per cascade, to render the shadow maps into.
+#[cfg(feature = "texture")]
+pub fn create_shadow_maps(device: &wgpu::Device, settings: CascadeSettings) -> Vec<crate::TextureAsset> {
+	let size = Extent2::new(settings.resolution, settings.resolution);
+	(0..settings.count)
+		.map(|i| {
+			let label = format!("Shadow cascade {i}");
+			crate::TextureAsset::create_depth_texture(device, size, Some(&label))
+		})
+		.collect()
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{Degrees, Radians};
+
+	const FRUSTUM: Frustum = Frustum {
+		y_fov: Radians::new(1.),
+		z_near: 1.,
+		z_far: 1000.,
+	};
+	const SETTINGS: CascadeSettings = CascadeSettings {
+		count: 4,
+		split: CascadeSplit::Practical(0.75),
+		resolution: 2048,
+		caster_distance: 50.,
+	};
+
+	fn cascades(position: Vec3<f32>, yaw: f32) -> Vec<ShadowCascade> {
+		let direction = Direction {
+			yaw: Degrees::new(yaw).into(),
+			pitch: Degrees::new(-20.).into(),
+		};
+		let light_direction = Vec3::new(1., -2., 0.5);
+		calc_shadow_cascades(
+			Position(position),
+			direction,
+			FRUSTUM,
+			ScreenSize::new(1920, 1080),
+			light_direction,
+			SETTINGS,
+		)
+	}
+
+	#[rstest]
+	#[case(CascadeSplit::Uniform,          1., 101.,  vec![26., 51., 76., 101.])]
+	#[case(CascadeSplit::Logarithmic,      1., 1000., vec![10., 100., 1000.])]
+	#[case(CascadeSplit::Practical(0.),    1., 1000., vec![334., 667., 1000.])]
+	#[case(CascadeSplit::Practical(1.),    1., 1000., vec![10., 100., 1000.])]
+	#[case(CascadeSplit::Practical(0.5),   1., 1000., vec![172., 383.5, 1000.])]
+	fn split_distances(
+		#[case] split: CascadeSplit,
+		#[case] z_near: f32,
+		#[case] z_far: f32,
+		#[case] expected: Vec<f32>,
+	) {
+		let distances = split.distances(z_near, z_far, expected.len());
+		assert_relative_eq!(distances.as_slice(), expected.as_slice(), epsilon = 1e-2);
+	}
+
+	#[test]
+	fn cascades_are_contiguous() {
+		let cascades = cascades(Vec3::zero(), 0.);
+		assert_eq!(cascades.len(), 4);
+		assert_eq!(cascades[0].near, FRUSTUM.z_near);
+		assert_eq!(cascades[3].far, FRUSTUM.z_far);
+		for pair in cascades.windows(2) {
+			assert_eq!(pair[0].far, pair[1].near);
+			assert!(pair[0].texel_size < pair[1].texel_size);
+		}
+	}
+
+	#[test]
+	fn cascades_contain_their_slice() {
+		let (position, direction) = (Position(Vec3::new(3., 10., -7.)), Direction::default());
+		let size = ScreenSize::new(1920, 1080);
+		let cascades = calc_shadow_cascades(
+			position,
+			direction,
+			FRUSTUM,
+			size,
+			Vec3::new(1., -2., 0.5),
+			SETTINGS,
+		);
+
+		for cascade in cascades {
+			let slice = Frustum {
+				z_near: cascade.near,
+				z_far: cascade.far,
+				..FRUSTUM
+			};
+			let view_projection = calc_projection_matrix(slice, size, None) * calc_view_matrix(position, direction);
+
+			for corner in calc_frustum_corners(view_projection) {
+				let ndc = cascade.view_projection * corner.with_w(1.);
+				assert!(ndc.x.abs() <= 1. && ndc.y.abs() <= 1., "{ndc:?}");
+				assert!((0. ..=1.).contains(&ndc.z), "{ndc:?}");
+			}
+		}
+	}
+
+	#[test]
+	fn stable_under_rotation() {
+		let texel_sizes = |yaw| cascades(Vec3::zero(), yaw).iter().map(|c| c.texel_size).collect::<Vec<_>>();
+		assert_eq!(texel_sizes(0.), texel_sizes(37.));
+		assert_eq!(texel_sizes(0.), texel_sizes(-120.));
+	}
+
+	#[rstest]
+	#[case(Vec3::new(0., 0., 0.))]
+	#[case(Vec3::new(0.013, 0., 0.))]
+	#[case(Vec3::new(12.34, 5.6, -7.89))]
+	fn snapped_to_texels(#[case] position: Vec3<f32>) {
+		for cascade in cascades(position, 15.) {
+			let origin = (cascade.view_projection * Vec4::unit_w()).xy() * SETTINGS.resolution as f32 / 2.;
+			assert_relative_eq!(origin, origin.round(), epsilon = 1e-2);
+		}
+	}
+}
@@ -142,9 +142,144 @@ pub trait DurationConverter {
 --------------------------------------------------------------------------------
 */
 
+/// How a fractional amount of units is rounded into a [`DiscreteUnits`] type.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+	Floor,
+	Ceil,
+	#[default]
+	Nearest,
+	TowardZero,
+}
+
+impl Rounding {
+	pub fn apply(self, value: f64) -> f64 {
+		match self {
+			Rounding::Floor => value.floor(),
+			Rounding::Ceil => value.ceil(),
+			Rounding::Nearest => value.round(),
+			Rounding::TowardZero => value.trunc(),
+		}
+	}
+}
+
+/// Unit types that can only represent discrete steps, like integers (tiles,
+/// pixels) or fixed-point numbers.
+///
+/// Multiplying or dividing them by a [`Duration`] goes through [`f64`] and is
+/// rounded with [`Rounding::Nearest`]. Use [`Speed::new_rounded`] and
+/// [`Speed::mul_rounded`] to pick another rounding, or a [`SpeedAccumulator`]
+/// to keep the lost remainder from one frame to the next.
+pub trait DiscreteUnits: Copy {
+	fn to_f64(self) -> f64;
+	fn from_f64(value: f64, rounding: Rounding) -> Self;
+}
+
+/// The [`DurationConverter`] output of [`DiscreteUnits`]: a duration in seconds
+/// that rounds the result when multiplying or dividing units by it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct DiscreteSecs(pub f64);
+
+impl<T: DiscreteUnits> Speed<T> {
+	pub fn new_rounded(units: T, duration: Duration, rounding: Rounding) -> Self {
+		Self::new_per_second(T::from_f64(units.to_f64() / duration.as_secs_f64(), rounding))
+	}
+
+	/// Like `speed * duration`, but with an explicit rounding.
+	pub fn mul_rounded(self, duration: Duration, rounding: Rounding) -> T {
+		T::from_f64(self.units_per_second.to_f64() * duration.as_secs_f64(), rounding)
+	}
+}
+
+/// Turns a [`Speed`] of [`DiscreteUnits`] into whole units over successive
+/// durations, carrying the rounding remainder over to the next call so that
+/// sub-unit movement isn't lost frame to frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SpeedAccumulator {
+	pub rounding: Rounding,
+	remainder: f64,
+}
+
+impl SpeedAccumulator {
+	pub fn new(rounding: Rounding) -> Self {
+		Self {
+			rounding,
+			remainder: 0.,
+		}
+	}
+
+	/// The fraction of a unit that hasn't been handed out yet.
+	pub fn remainder(&self) -> f64 {
+		self.remainder
+	}
+
+	pub fn reset(&mut self) {
+		self.remainder = 0.;
+	}
+
+	/// Returns the whole units travelled at `speed` over `duration`, including
+	/// the remainder of the previous calls.
+	pub fn advance<T: DiscreteUnits>(&mut self, speed: Speed<T>, duration: Duration) -> T {
+		let exact = speed.per_second().to_f64() * duration.as_secs_f64() + self.remainder;
+		let units = T::from_f64(exact, self.rounding);
+		self.remainder = exact - units.to_f64();
+		units
+	}
+}
+
+macro_rules! impl_discrete_units {
+	($($type:ty),*) => {$(
+		#[rustfmt::skip] impl DiscreteUnits             for $type {fn to_f64(self) -> f64 {self as f64} fn from_f64(value: f64, rounding: Rounding) -> Self {rounding.apply(value) as $type}}
+		#[rustfmt::skip] impl DurationConverter         for $type {type Output = DiscreteSecs; fn as_secs(d: Duration) -> Self::Output {DiscreteSecs(d.as_secs_f64())}}
+		#[rustfmt::skip] impl Mul<DiscreteSecs>         for $type {type Output = Self; fn mul(self, rhs: DiscreteSecs) -> Self {Self::from_f64(self.to_f64() * rhs.0, Rounding::Nearest)}}
+		#[rustfmt::skip] impl Div<DiscreteSecs>         for $type {type Output = Self; fn div(self, rhs: DiscreteSecs) -> Self {Self::from_f64(self.to_f64() / rhs.0, Rounding::Nearest)}}
+	)*};
+}
+
+impl_discrete_units!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+#[cfg(feature = "fixed")]
+macro_rules! impl_discrete_units_fixed {
+	($($type:ident: $frac:ident),*) => {$(
+		/// Rounds at the precision of the fixed-point type, i.e. to a multiple of
+		/// its smallest representable step. Like the integer casts, values out
+		/// of range saturate and NaN becomes zero.
+		impl<F: fixed::types::extra::$frac> DiscreteUnits for fixed::$type<F> {
+			fn to_f64(self) -> f64 {
+				self.to_num()
+			}
+
+			fn from_f64(value: f64, rounding: Rounding) -> Self {
+				if value.is_nan() {
+					return Self::ZERO;
+				}
+				let scale = (Self::FRAC_NBITS as f64).exp2();
+				Self::saturating_from_num(rounding.apply(value * scale) / scale)
+			}
+		}
+
+		#[rustfmt::skip] impl<F: fixed::types::extra::$frac> DurationConverter for fixed::$type<F> {type Output = DiscreteSecs; fn as_secs(d: Duration) -> Self::Output {DiscreteSecs(d.as_secs_f64())}}
+		#[rustfmt::skip] impl<F: fixed::types::extra::$frac> Mul<DiscreteSecs>  for fixed::$type<F> {type Output = Self; fn mul(self, rhs: DiscreteSecs) -> Self {Self::from_f64(self.to_f64() * rhs.0, Rounding::Nearest)}}
+		#[rustfmt::skip] impl<F: fixed::types::extra::$frac> Div<DiscreteSecs>  for fixed::$type<F> {type Output = Self; fn div(self, rhs: DiscreteSecs) -> Self {Self::from_f64(self.to_f64() / rhs.0, Rounding::Nearest)}}
+	)*};
+}
+
+#[cfg(feature = "fixed")]
+impl_discrete_units_fixed!(
+	FixedI8: LeEqU8, FixedI16: LeEqU16, FixedI32: LeEqU32, FixedI64: LeEqU64,
+	FixedU8: LeEqU8, FixedU16: LeEqU16, FixedU32: LeEqU32, FixedU64: LeEqU64
+);
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
 #[cfg(test)]
 mod tests {
 	use approx::assert_relative_eq;
+	use rstest::rstest;
 
 	use super::*;
 
@@ -168,4 +303,84 @@ mod tests {
 		assert_relative_eq!((Speed::new_per_second(Degrees::new(10.)) * Duration::new(1, 0)).degrees(), 10.);
 		assert_relative_eq!((Speed::new_per_second(Degrees::new(0.1)) * Duration::new(1, 0)).degrees(), 0.1);
 	}
+
+	#[test]
+	fn test_duration_mult_integer() {
+		assert_eq!(Speed::new(10, Duration::new(2, 0)) * Duration::new(1, 0), 5);
+		assert_eq!(Speed::new_per_second(3_i32) * Duration::from_millis(500), 2);
+		assert_eq!(Speed::new_per_second(3_u32) * Duration::from_millis(100), 0);
+	}
+
+	#[rstest]
+	#[case(Rounding::Floor,      1)]
+	#[case(Rounding::Ceil,       2)]
+	#[case(Rounding::Nearest,    2)]
+	#[case(Rounding::TowardZero, 1)]
+	fn test_duration_mult_rounding(#[case] rounding: Rounding, #[case] expected: i32) {
+		assert_eq!(Speed::new_per_second(3).mul_rounded(Duration::from_millis(500), rounding), expected);
+		assert_eq!(Speed::new_per_second(-3).mul_rounded(Duration::from_millis(500), rounding), match rounding {
+			Rounding::Floor => -2,
+			Rounding::Ceil => -1,
+			Rounding::Nearest => -2,
+			Rounding::TowardZero => -1,
+		});
+	}
+
+	// A frame is rounded up to the nanosecond, so the exact total is just above
+	// 30 and rounding up hands out one more unit
+	#[rstest]
+	#[case(Rounding::Floor,      30)]
+	#[case(Rounding::Ceil,       31)]
+	#[case(Rounding::Nearest,    30)]
+	#[case(Rounding::TowardZero, 30)]
+	fn test_accumulator(#[case] rounding: Rounding, #[case] expected: i32) {
+		let speed = Speed::new_per_second(3_i32);
+		let mut accumulator = SpeedAccumulator::new(rounding);
+
+		let frame = Duration::from_secs_f64(1. / 60.);
+		let total: i32 = (0..600).map(|_| accumulator.advance(speed, frame)).sum();
+		assert_eq!(total, expected);
+		assert!(accumulator.remainder().abs() < 1.);
+
+		// Without carrying the remainder, every frame would be rounded down to
+		// nothing, or up to a whole unit
+		let expected = if rounding == Rounding::Ceil { 1 } else { 0 };
+		assert_eq!(speed.mul_rounded(frame, rounding), expected);
+	}
+
+	#[test]
+	#[cfg(feature = "fixed")]
+	fn test_duration_mult_fixed() {
+		use fixed::types::I16F16;
+
+		let speed = Speed::new(I16F16::from_num(10), Duration::new(4, 0));
+		assert_eq!(speed.per_second(), I16F16::from_num(2.5));
+		assert_eq!(speed * Duration::from_millis(500), I16F16::from_num(1.25));
+
+		let mut accumulator = SpeedAccumulator::new(Rounding::Floor);
+		let speed = Speed::new_per_second(I16F16::from_num(1) / 3);
+		let total: I16F16 = (0..3).map(|_| accumulator.advance(speed, Duration::new(1, 0))).sum();
+		assert_eq!(total, speed.per_second() * 3);
+	}
+
+	#[test]
+	fn test_overflow_saturates() {
+		assert_eq!(Speed::new_per_second(100_i8).mul_rounded(Duration::new(10, 0), Rounding::Ceil), i8::MAX);
+		assert_eq!(Speed::new_per_second(-100_i8).mul_rounded(Duration::new(10, 0), Rounding::Floor), i8::MIN);
+		assert_eq!(Speed::new(5_u32, Duration::ZERO).per_second(), u32::MAX);
+	}
+
+	#[test]
+	#[cfg(feature = "fixed")]
+	fn test_overflow_saturates_fixed() {
+		use fixed::types::{I16F16, U8F8};
+
+		let speed = Speed::new_per_second(I16F16::from_num(30000));
+		assert_eq!(speed.mul_rounded(Duration::new(10, 0), Rounding::Nearest), I16F16::MAX);
+		assert_eq!(Speed::new_per_second(-speed.per_second()) * Duration::new(10, 0), I16F16::MIN);
+
+		// An infinite rate from a zero duration
+		assert_eq!(Speed::new(U8F8::from_num(1), Duration::ZERO).per_second(), U8F8::MAX);
+		assert_eq!(U8F8::from_f64(f64::NAN, Rounding::Nearest), U8F8::ZERO);
+	}
 }
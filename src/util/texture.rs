@@ -1,12 +1,17 @@
 #![allow(dead_code)]
 
-use anyhow::{Ok, Result};
-use image::GenericImageView;
+use std::{borrow::Cow, collections::HashMap, iter};
+
+use anyhow::{bail, ensure, Ok, Result};
+use image::{
+	imageops::{self, FilterType},
+	GenericImageView, Rgba, Rgba32FImage, RgbaImage,
+};
 use vek::Extent2;
 use wgpu::{
 	naga::back::msl::sampler::Filter, AddressMode, CompareFunction, Device, Extent3d, FilterMode, ImageCopyTexture,
 	ImageDataLayout, Origin3d, Queue, Sampler, SamplerBorderColor, SamplerDescriptor, TextureAspect, TextureDescriptor,
-	TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor,
+	TextureDimension, TextureFormat, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension,
 };
 
 /*
@@ -21,6 +26,55 @@ pub struct TextureAsset {
 	pub sampler: Sampler,
 }
 
+/// How the smaller mip levels are computed from the bigger ones.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MipFilter {
+	/// Averages the pixels of the previous level, fast and good enough for
+	/// most textures.
+	#[default]
+	Box,
+	/// Resamples the full-size image with a Lanczos filter, keeping the small
+	/// levels sharper.
+	Lanczos,
+}
+
+/// The format a loaded image is converted to on the CPU before it is uploaded.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TextureLoadFormat {
+	/// Only the red channel, like for roughness or height maps.
+	R8,
+	/// The red and green channels, like for two-channel normal maps.
+	Rg8,
+	/// Linear colors or data, like normal maps.
+	Rgba8,
+	/// sRGB colors, like albedo textures.
+	#[default]
+	Rgba8Srgb,
+	/// High dynamic range colors, with half the memory of
+	/// [`Rgba32Float`](Self::Rgba32Float).
+	Rgba16Float,
+	/// Can't be sampled with a linear filter without
+	/// [`Features::FLOAT32_FILTERABLE`](wgpu::Features::FLOAT32_FILTERABLE).
+	Rgba32Float,
+}
+
+/// How an image is converted, uploaded and sampled.
+///
+/// A [`FilterMode`] converts into the default options with all the filters set
+/// to it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureLoadOptions {
+	/// Prefer [`with_format`](Self::with_format), which also adjusts the
+	/// filters to the format.
+	pub format: TextureLoadFormat,
+	pub address_mode: AddressMode,
+	pub mag_filter: FilterMode,
+	pub min_filter: FilterMode,
+	pub mipmap_filter: FilterMode,
+	/// Generates a full mip chain on the CPU with this filter.
+	pub mipmaps: Option<MipFilter>,
+}
+
 impl TextureAsset {
 	pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
 
@@ -28,67 +82,75 @@ impl TextureAsset {
 		device: &Device,
 		queue: &Queue,
 		bytes: &[u8],
-		filter_mode: FilterMode,
+		options: impl Into<TextureLoadOptions>,
 		label: Option<&str>,
 	) -> Result<Self> {
 		let img = image::load_from_memory(bytes)?;
-		Self::from_image(device, queue, &img, filter_mode, label)
+		Self::from_image(device, queue, &img, options, label)
 	}
 
 	pub fn from_image(
 		device: &Device,
 		queue: &Queue,
 		img: &image::DynamicImage,
-		filter_mode: FilterMode,
+		options: impl Into<TextureLoadOptions>,
 		label: Option<&str>,
 	) -> Result<Self> {
-		let rgba = img.to_rgba8();
-		let dimensions = img.dimensions();
+		let options = options.into();
+		let levels = convert_image_levels(img, &options);
+		Ok(Self::from_mip_levels(device, queue, &levels, &options, label))
+	}
 
+	/// Uploads every level of a mip chain, starting with the full-size image.
+	fn from_mip_levels(
+		device: &Device,
+		queue: &Queue,
+		levels: &[(Extent2<u32>, Vec<u8>)],
+		options: &TextureLoadOptions,
+		label: Option<&str>,
+	) -> Self {
 		let size = Extent3d {
-			width: dimensions.0,
-			height: dimensions.1,
+			width: levels[0].0.w,
+			height: levels[0].0.h,
 			depth_or_array_layers: 1,
 		};
 		let texture = device.create_texture(&TextureDescriptor {
 			label,
 			size,
-			mip_level_count: 1,
+			mip_level_count: levels.len() as u32,
 			sample_count: 1,
 			dimension: TextureDimension::D2,
-			format: TextureFormat::Rgba8UnormSrgb,
+			format: options.format.texture_format(),
 			usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
 			view_formats: &[],
 		});
 
-		queue.write_texture(
-			ImageCopyTexture {
-				aspect: TextureAspect::All,
-				texture: &texture,
-				mip_level: 0,
-				origin: Origin3d::ZERO,
-			},
-			&rgba,
-			ImageDataLayout {
-				offset: 0,
-				bytes_per_row: Some(4 * dimensions.0),
-				rows_per_image: Some(dimensions.1),
-			},
-			size,
-		);
+		for (mip_level, (Extent2 { w, h }, bytes)) in levels.iter().enumerate() {
+			queue.write_texture(
+				ImageCopyTexture {
+					aspect: TextureAspect::All,
+					texture: &texture,
+					mip_level: mip_level as u32,
+					origin: Origin3d::ZERO,
+				},
+				bytes,
+				ImageDataLayout {
+					offset: 0,
+					bytes_per_row: Some(options.format.bytes_per_pixel() * w),
+					rows_per_image: Some(*h),
+				},
+				Extent3d {
+					width: *w,
+					height: *h,
+					depth_or_array_layers: 1,
+				},
+			);
+		}
 
 		let view = texture.create_view(&TextureViewDescriptor::default());
-		let sampler = device.create_sampler(&SamplerDescriptor {
-			address_mode_u: AddressMode::Repeat,
-			address_mode_v: AddressMode::Repeat,
-			address_mode_w: AddressMode::Repeat,
-			mag_filter: filter_mode,
-			min_filter: filter_mode,
-			mipmap_filter: filter_mode,
-			..Default::default()
-		});
+		let sampler = options.create_sampler(device);
 
-		Ok(Self { texture, view, sampler })
+		Self { texture, view, sampler }
 	}
 
 	pub fn create_depth_texture(device: &Device, size: Extent2<u32>, label: Option<&str>) -> Self {
@@ -172,6 +234,301 @@ impl TextureAsset {
 	}
 }
 
+impl TextureLoadFormat {
+	pub fn texture_format(self) -> TextureFormat {
+		match self {
+			TextureLoadFormat::R8 => TextureFormat::R8Unorm,
+			TextureLoadFormat::Rg8 => TextureFormat::Rg8Unorm,
+			TextureLoadFormat::Rgba8 => TextureFormat::Rgba8Unorm,
+			TextureLoadFormat::Rgba8Srgb => TextureFormat::Rgba8UnormSrgb,
+			TextureLoadFormat::Rgba16Float => TextureFormat::Rgba16Float,
+			TextureLoadFormat::Rgba32Float => TextureFormat::Rgba32Float,
+		}
+	}
+
+	pub fn bytes_per_pixel(self) -> u32 {
+		match self {
+			TextureLoadFormat::R8 => 1,
+			TextureLoadFormat::Rg8 => 2,
+			TextureLoadFormat::Rgba8 | TextureLoadFormat::Rgba8Srgb => 4,
+			TextureLoadFormat::Rgba16Float => 8,
+			TextureLoadFormat::Rgba32Float => 16,
+		}
+	}
+
+	/// Whether the texture can be sampled with a linear filter without
+	/// enabling any feature.
+	pub fn is_filterable(self) -> bool {
+		self != TextureLoadFormat::Rgba32Float
+	}
+
+	/// Whether the colors are stored in sRGB, and decoded to linear when
+	/// sampled.
+	pub fn is_srgb(self) -> bool {
+		self == TextureLoadFormat::Rgba8Srgb
+	}
+}
+
+impl TextureLoadOptions {
+	/// Sets the format, falling back to [`FilterMode::Nearest`] for the
+	/// formats that can't be filtered by default. Call
+	/// [`with_filter`](Self::with_filter) afterwards to filter them anyway.
+	pub fn with_format(mut self, format: TextureLoadFormat) -> Self {
+		self.format = format;
+		if !format.is_filterable() {
+			self = self.with_filter(FilterMode::Nearest);
+		}
+		self
+	}
+
+	pub fn with_address_mode(mut self, address_mode: AddressMode) -> Self {
+		self.address_mode = address_mode;
+		self
+	}
+
+	/// Sets the magnification, minification and mipmap filters at once.
+	pub fn with_filter(mut self, filter_mode: FilterMode) -> Self {
+		self.mag_filter = filter_mode;
+		self.min_filter = filter_mode;
+		self.mipmap_filter = filter_mode;
+		self
+	}
+
+	/// Generates a full mip chain on the CPU with [`generate_mipmaps`] when the
+	/// texture is loaded.
+	pub fn with_mipmaps(mut self, mip_filter: MipFilter) -> Self {
+		self.mipmaps = Some(mip_filter);
+		self
+	}
+
+	pub fn create_sampler(&self, device: &Device) -> Sampler {
+		device.create_sampler(&SamplerDescriptor {
+			address_mode_u: self.address_mode,
+			address_mode_v: self.address_mode,
+			address_mode_w: self.address_mode,
+			mag_filter: self.mag_filter,
+			min_filter: self.min_filter,
+			mipmap_filter: self.mipmap_filter,
+			..Default::default()
+		})
+	}
+}
+
+impl Default for TextureLoadOptions {
+	fn default() -> Self {
+		Self {
+			format: TextureLoadFormat::default(),
+			address_mode: AddressMode::Repeat,
+			mag_filter: FilterMode::Linear,
+			min_filter: FilterMode::Linear,
+			mipmap_filter: FilterMode::Linear,
+			mipmaps: None,
+		}
+	}
+}
+
+impl From<FilterMode> for TextureLoadOptions {
+	fn from(filter_mode: FilterMode) -> Self {
+		Self::default().with_filter(filter_mode)
+	}
+}
+
+/// Converts an image to the bytes of each level of a texture, with a full mip
+/// chain if the options ask for it.
+pub fn convert_image_levels(image: &image::DynamicImage, options: &TextureLoadOptions) -> Vec<(Extent2<u32>, Vec<u8>)> {
+	// 8-bit images are uploaded as-is without mipmaps, which is the common case
+	if options.mipmaps.is_none() && matches!(options.format, TextureLoadFormat::Rgba8 | TextureLoadFormat::Rgba8Srgb) {
+		return vec![(Extent2::from(image.dimensions()), image.to_rgba8().into_raw())];
+	}
+
+	let base = if options.format.is_srgb() {
+		srgb_to_linear_image(&image.to_rgba8())
+	} else {
+		image.to_rgba32f()
+	};
+
+	let levels = match options.mipmaps {
+		Some(mip_filter) => generate_linear_mipmaps(base, mip_filter),
+		None => vec![base],
+	};
+
+	levels
+		.iter()
+		.map(|level| (Extent2::from(level.dimensions()), convert_pixels(level, options.format)))
+		.collect()
+}
+
+/// Converts an image to the bytes of a texture of `format`.
+pub fn convert_image(image: &image::DynamicImage, format: TextureLoadFormat) -> Vec<u8> {
+	let options = TextureLoadOptions::default().with_format(format);
+	convert_image_levels(image, &options).swap_remove(0).1
+}
+
+/// Encodes linear pixels, dropping the channels the format doesn't have.
+fn convert_pixels(image: &Rgba32FImage, format: TextureLoadFormat) -> Vec<u8> {
+	let unorm = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+
+	let pixel_count = image.width() as usize * image.height() as usize;
+	let mut bytes = Vec::with_capacity(pixel_count * format.bytes_per_pixel() as usize);
+	for &Rgba([r, g, b, a]) in image.pixels() {
+		match format {
+			TextureLoadFormat::R8 => bytes.push(unorm(r)),
+			TextureLoadFormat::Rg8 => bytes.extend_from_slice(&[unorm(r), unorm(g)]),
+			TextureLoadFormat::Rgba8 => bytes.extend_from_slice(&[unorm(r), unorm(g), unorm(b), unorm(a)]),
+			TextureLoadFormat::Rgba8Srgb => {
+				bytes.extend_from_slice(&[linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), unorm(a)])
+			}
+			TextureLoadFormat::Rgba16Float => {
+				for channel in [r, g, b, a] {
+					bytes.extend_from_slice(&f32_to_f16(channel).to_le_bytes());
+				}
+			}
+			TextureLoadFormat::Rgba32Float => {
+				for channel in [r, g, b, a] {
+					bytes.extend_from_slice(&channel.to_le_bytes());
+				}
+			}
+		}
+	}
+
+	bytes
+}
+
+/// The bits of the closest half-precision float, rounding to even.
+fn f32_to_f16(value: f32) -> u16 {
+	let bits = value.to_bits();
+	let sign = ((bits >> 16) & 0x8000) as u16;
+	let exponent = ((bits >> 23) & 0xff) as i32;
+	let mantissa = bits & 0x7f_ffff;
+
+	// Infinities and NaNs
+	if exponent == 0xff {
+		return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+	}
+
+	let exponent = exponent - 127 + 15;
+	if exponent >= 0x1f {
+		return sign | 0x7c00;
+	}
+
+	// Subnormals lose the implicit leading bit of the mantissa
+	let (half, mantissa, shift) = if exponent <= 0 {
+		if exponent < -10 {
+			return sign;
+		}
+		(0, mantissa | 0x80_0000, (14 - exponent) as u32)
+	} else {
+		((exponent as u32) << 10, mantissa, 13)
+	};
+
+	let half = half | (mantissa >> shift);
+	let (remainder, halfway) = (mantissa & ((1 << shift) - 1), 1 << (shift - 1));
+	// Rounding up can carry into the exponent, which is still correct
+	let half = if remainder > halfway || (remainder == halfway && half & 1 == 1) {
+		half + 1
+	} else {
+		half
+	};
+
+	sign | half as u16
+}
+
+/// The number of levels of a full mip chain, down to 1x1.
+pub fn calc_mip_level_count(size: Extent2<u32>) -> u32 {
+	u32::BITS - size.w.max(size.h).max(1).leading_zeros()
+}
+
+/// The size of a mip level, each level halving the previous one (rounding
+/// down) without going under 1.
+pub fn calc_mip_level_size(size: Extent2<u32>, level: u32) -> Extent2<u32> {
+	size.map(|side| side.checked_shr(level).unwrap_or(0).max(1))
+}
+
+/// The full mip chain of an sRGB image, starting with the image itself.
+///
+/// The pixels are averaged in linear space, so that the levels don't get
+/// darker than the full-size image. The alpha is averaged as-is.
+pub fn generate_mipmaps(image: &RgbaImage, filter: MipFilter) -> Vec<RgbaImage> {
+	let levels = generate_linear_mipmaps(srgb_to_linear_image(image), filter);
+	iter::once(image.clone())
+		.chain(levels[1..].iter().map(linear_to_srgb_image))
+		.collect()
+}
+
+/// The full mip chain of an image in linear space, starting with the image
+/// itself.
+fn generate_linear_mipmaps(base: Rgba32FImage, filter: MipFilter) -> Vec<Rgba32FImage> {
+	let size = Extent2::new(base.width(), base.height());
+
+	let mut levels = vec![base];
+	for level in 1..calc_mip_level_count(size) {
+		let Extent2 { w, h } = calc_mip_level_size(size, level);
+		let next = match filter {
+			MipFilter::Box => downsample_box(&levels[level as usize - 1], w, h),
+			MipFilter::Lanczos => imageops::resize(&levels[0], w, h, FilterType::Lanczos3),
+		};
+		levels.push(next);
+	}
+
+	levels
+}
+
+/// Averages the block of pixels of `image` covered by each pixel of the result.
+/// With odd sizes, the last block of each row or column is one pixel bigger.
+fn downsample_box(image: &Rgba32FImage, width: u32, height: u32) -> Rgba32FImage {
+	let (source_width, source_height) = image.dimensions();
+	let range = |i: u32, size: u32, source_size: u32| (i * source_size / size)..((i + 1) * source_size / size);
+
+	Rgba32FImage::from_fn(width, height, |x, y| {
+		let mut sum = [0.; 4];
+		let mut count = 0.;
+		for source_y in range(y, height, source_height) {
+			for source_x in range(x, width, source_width) {
+				let pixel = image.get_pixel(source_x, source_y);
+				for (sum, channel) in sum.iter_mut().zip(pixel.0) {
+					*sum += channel;
+				}
+				count += 1.;
+			}
+		}
+		Rgba(sum.map(|sum| sum / count))
+	})
+}
+
+fn srgb_to_linear_image(image: &RgbaImage) -> Rgba32FImage {
+	Rgba32FImage::from_fn(image.width(), image.height(), |x, y| {
+		let [r, g, b, a] = image.get_pixel(x, y).0;
+		Rgba([srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a as f32 / 255.])
+	})
+}
+
+fn linear_to_srgb_image(image: &Rgba32FImage) -> RgbaImage {
+	RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+		let [r, g, b, a] = image.get_pixel(x, y).0;
+		Rgba([linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), (a.clamp(0., 1.) * 255.).round() as u8])
+	})
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+	let value = value as f32 / 255.;
+	if value <= 0.04045 {
+		value / 12.92
+	} else {
+		((value + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+	// Lanczos overshoots around sharp edges
+	let value = value.clamp(0., 1.);
+	let value = if value <= 0.003_130_8 {
+		value * 12.92
+	} else {
+		value.powf(1. / 2.4) * 1.055 - 0.055
+	};
+	(value * 255.).round() as u8
+}
+
 /*
 --------------------------------------------------------------------------------
 ||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
@@ -187,12 +544,13 @@ impl TextureArray {
 		device: &Device,
 		queue: &Queue,
 		array: Vec<(&[u8], Option<&str>)>,
-		filter_mode: FilterMode,
+		options: impl Into<TextureLoadOptions>,
 	) -> Result<Self> {
+		let options = options.into();
 		let mut textures = Vec::new();
 
 		for (bytes, label) in array {
-			textures.push(TextureAsset::from_bytes(device, queue, bytes, filter_mode, label)?);
+			textures.push(TextureAsset::from_bytes(device, queue, bytes, options, label)?);
 		}
 
 		Ok(TextureArray { textures })
@@ -202,13 +560,14 @@ impl TextureArray {
 	pub fn from_images(
 		device: &Device,
 		queue: &Queue,
-		filter_mode: FilterMode,
+		options: impl Into<TextureLoadOptions>,
 		array: Vec<(&image::DynamicImage, Option<&str>)>,
 	) -> Result<Self> {
+		let options = options.into();
 		let mut textures = Vec::new();
 
 		for (image, label) in array {
-			textures.push(TextureAsset::from_image(device, queue, image, filter_mode, label)?);
+			textures.push(TextureAsset::from_image(device, queue, image, options, label)?);
 		}
 
 		Ok(TextureArray { textures })
@@ -231,3 +590,391 @@ impl TextureArray {
 		self.len() == 0
 	}
 }
+
+/// How [`LayeredTextureArray`] handles images of different sizes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LayerSize {
+	/// All the images must have the same size.
+	#[default]
+	Exact,
+	/// The images are resized to this size when they don't already have it.
+	Resize(Extent2<u32>),
+}
+
+/// A single texture with one layer per image, bound as a `texture_2d_array`
+/// in WGSL with one view and one sampler.
+pub struct LayeredTextureArray {
+	pub texture: wgpu::Texture,
+	pub view: TextureView,
+	pub sampler: Sampler,
+	size: Extent2<u32>,
+	layer_count: u32,
+	labels: HashMap<String, u32>,
+}
+
+impl LayeredTextureArray {
+	pub fn from_bytes(
+		device: &Device,
+		queue: &Queue,
+		array: Vec<(&[u8], Option<&str>)>,
+		layer_size: LayerSize,
+		options: impl Into<TextureLoadOptions>,
+		label: Option<&str>,
+	) -> Result<Self> {
+		let images = array
+			.into_iter()
+			.map(|(bytes, label)| Ok((image::load_from_memory(bytes)?, label)))
+			.collect::<Result<Vec<_>>>()?;
+		let array = images.iter().map(|(image, label)| (image, *label)).collect();
+		Self::from_images(device, queue, array, layer_size, options, label)
+	}
+
+	/// Uploads the images as the layers of one texture, in order. The labels
+	/// of the images must be unique, and can then be used to find their layer.
+	pub fn from_images(
+		device: &Device,
+		queue: &Queue,
+		array: Vec<(&image::DynamicImage, Option<&str>)>,
+		layer_size: LayerSize,
+		options: impl Into<TextureLoadOptions>,
+		label: Option<&str>,
+	) -> Result<Self> {
+		let options = options.into();
+		let max_layers = device.limits().max_texture_array_layers as usize;
+		ensure!(array.len() <= max_layers, "{} layers is more than the maximum of {max_layers}", array.len());
+
+		let labels = calc_layer_labels(array.iter().map(|(_, label)| *label))?;
+		let (size, layers) = prepare_layers(array.iter().map(|(image, _)| *image), layer_size)?;
+		let layer_count = layers.len() as u32;
+
+		let mip_level_count = match options.mipmaps {
+			Some(_) => calc_mip_level_count(size),
+			None => 1,
+		};
+		let texture = device.create_texture(&TextureDescriptor {
+			label,
+			size: Extent3d {
+				width: size.w,
+				height: size.h,
+				depth_or_array_layers: layer_count,
+			},
+			mip_level_count,
+			sample_count: 1,
+			dimension: TextureDimension::D2,
+			format: options.format.texture_format(),
+			usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+			view_formats: &[],
+		});
+
+		for (layer, image) in layers.iter().enumerate() {
+			for (mip_level, (Extent2 { w, h }, bytes)) in convert_image_levels(image, &options).iter().enumerate() {
+				queue.write_texture(
+					ImageCopyTexture {
+						aspect: TextureAspect::All,
+						texture: &texture,
+						mip_level: mip_level as u32,
+						origin: Origin3d {
+							x: 0,
+							y: 0,
+							z: layer as u32,
+						},
+					},
+					bytes,
+					ImageDataLayout {
+						offset: 0,
+						bytes_per_row: Some(options.format.bytes_per_pixel() * w),
+						rows_per_image: Some(*h),
+					},
+					Extent3d {
+						width: *w,
+						height: *h,
+						depth_or_array_layers: 1,
+					},
+				);
+			}
+		}
+
+		// A single layer would get a plain 2D view by default
+		let view = texture.create_view(&TextureViewDescriptor {
+			dimension: Some(TextureViewDimension::D2Array),
+			..Default::default()
+		});
+		let sampler = options.create_sampler(device);
+
+		Ok(Self {
+			texture,
+			view,
+			sampler,
+			size,
+			layer_count,
+			labels,
+		})
+	}
+
+	/// The index of the layer of the image with this label.
+	pub fn layer(&self, label: &str) -> Option<u32> {
+		self.labels.get(label).copied()
+	}
+
+	/// The size of every layer.
+	pub fn size(&self) -> Extent2<u32> {
+		self.size
+	}
+
+	pub fn layer_count(&self) -> u32 {
+		self.layer_count
+	}
+}
+
+/// Gives all the images the same size to be the layers of a texture array,
+/// only copying the ones that are resized.
+pub fn prepare_layers<'a>(
+	images: impl IntoIterator<Item = &'a image::DynamicImage>,
+	layer_size: LayerSize,
+) -> Result<(Extent2<u32>, Vec<Cow<'a, image::DynamicImage>>)> {
+	let mut size = match layer_size {
+		LayerSize::Exact => None,
+		LayerSize::Resize(size) => Some(size),
+	};
+
+	let mut layers = Vec::new();
+	for (layer, image) in images.into_iter().enumerate() {
+		let (width, height) = image.dimensions();
+		let Extent2 { w, h } = *size.get_or_insert(Extent2::new(width, height));
+
+		let layer = if (width, height) == (w, h) {
+			Cow::Borrowed(image)
+		} else if layer_size == LayerSize::Exact {
+			bail!("layer {layer} is {width}x{height}, but the first one is {w}x{h}");
+		} else {
+			Cow::Owned(image.resize_exact(w, h, FilterType::Lanczos3))
+		};
+		layers.push(layer);
+	}
+
+	match size {
+		Some(size) if !layers.is_empty() => {
+			ensure!(size.w > 0 && size.h > 0, "the layers can't be empty");
+			Ok((size, layers))
+		}
+		_ => bail!("a texture array needs at least one layer"),
+	}
+}
+
+/// Maps the labels to the index of their layer, skipping the unlabelled ones.
+fn calc_layer_labels<'a>(labels: impl IntoIterator<Item = Option<&'a str>>) -> Result<HashMap<String, u32>> {
+	let mut map = HashMap::new();
+	for (layer, label) in labels.into_iter().enumerate() {
+		if let Some(label) = label {
+			ensure!(map.insert(label.to_owned(), layer as u32).is_none(), "duplicate layer label `{label}`");
+		}
+	}
+	Ok(map)
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+
+	#[rstest]
+	#[case(Extent2::new(1, 1),     1)]
+	#[case(Extent2::new(2, 1),     2)]
+	#[case(Extent2::new(256, 256), 9)]
+	#[case(Extent2::new(300, 100), 9)]
+	#[case(Extent2::new(100, 513), 10)]
+	fn mip_level_count(#[case] size: Extent2<u32>, #[case] expected: u32) {
+		assert_eq!(calc_mip_level_count(size), expected);
+	}
+
+	#[rstest]
+	#[case(0, Extent2::new(300, 100))]
+	#[case(1, Extent2::new(150, 50))]
+	#[case(3, Extent2::new(37, 12))]
+	#[case(7, Extent2::new(2, 1))]
+	#[case(8, Extent2::new(1, 1))]
+	#[case(40, Extent2::new(1, 1))]
+	fn mip_level_size(#[case] level: u32, #[case] expected: Extent2<u32>) {
+		assert_eq!(calc_mip_level_size(Extent2::new(300, 100), level), expected);
+	}
+
+	#[rstest]
+	#[case(MipFilter::Box)]
+	#[case(MipFilter::Lanczos)]
+	fn mip_chain_sizes(#[case] filter: MipFilter) {
+		let image = RgbaImage::from_pixel(37, 10, Rgba([10, 120, 250, 128]));
+		let levels = generate_mipmaps(&image, filter);
+
+		assert_eq!(levels.len(), 6);
+		assert_eq!(levels[0], image);
+		for (level, mip) in levels.iter().enumerate() {
+			let size = calc_mip_level_size(Extent2::new(37, 10), level as u32);
+			assert_eq!(mip.dimensions(), (size.w, size.h));
+			// A uniform image stays the same color
+			assert!(mip.pixels().all(|pixel| *pixel == Rgba([10, 120, 250, 128])), "level {level}");
+		}
+	}
+
+	#[test]
+	fn srgb_correct_average() {
+		// Black and white average to a mid grey in linear space, which is much
+		// brighter than 128 in sRGB
+		let image = RgbaImage::from_fn(2, 2, |x, _| Rgba([(x * 255) as u8, (x * 255) as u8, (x * 255) as u8, 255]));
+		let levels = generate_mipmaps(&image, MipFilter::Box);
+		assert_eq!(levels[1].get_pixel(0, 0), &Rgba([188, 188, 188, 255]));
+	}
+
+	#[test]
+	fn box_odd_size() {
+		let image = RgbaImage::from_fn(3, 1, |x, _| Rgba([0, 0, 0, [0, 255, 255][x as usize]]));
+		let levels = generate_mipmaps(&image, MipFilter::Box);
+		assert_eq!(levels[1].dimensions(), (1, 1));
+		assert_eq!(levels[1].get_pixel(0, 0).0[3], 170);
+	}
+
+	#[test]
+	fn layers_exact_size() {
+		let images = [
+			image::DynamicImage::new_rgba8(4, 2),
+			image::DynamicImage::new_rgb8(4, 2),
+			image::DynamicImage::new_rgba8(2, 4),
+		];
+
+		let (size, layers) = prepare_layers(&images[..2], LayerSize::Exact).unwrap();
+		assert_eq!(size, Extent2::new(4, 2));
+		assert_eq!(layers.len(), 2);
+		assert_eq!(layers[1].dimensions(), (4, 2));
+
+		assert!(prepare_layers(&images, LayerSize::Exact).is_err());
+		assert!(prepare_layers(&[], LayerSize::Exact).is_err());
+		assert!(prepare_layers(&[], LayerSize::Resize(Extent2::new(4, 4))).is_err());
+	}
+
+	#[test]
+	fn layers_resized() {
+		let images = [image::DynamicImage::new_rgba8(4, 2), image::DynamicImage::new_rgba8(16, 16)];
+		let (size, layers) = prepare_layers(&images, LayerSize::Resize(Extent2::new(8, 8))).unwrap();
+		assert_eq!(size, Extent2::new(8, 8));
+		assert!(layers.iter().all(|layer| layer.dimensions() == (8, 8)));
+	}
+
+	#[test]
+	fn layers_keep_precision() {
+		// Normal or HDR layers aren't squeezed into 8-bit sRGB before conversion
+		let images = [image::DynamicImage::ImageRgba32F(Rgba32FImage::from_pixel(2, 2, Rgba([2.5, 0., 0., 1.])))];
+		let (_, layers) = prepare_layers(&images, LayerSize::Exact).unwrap();
+		assert!(matches!(layers[0], Cow::Borrowed(_)));
+
+		let options = TextureLoadOptions::default().with_format(TextureLoadFormat::Rgba32Float);
+		let levels = convert_image_levels(&layers[0], &options);
+		assert_eq!(levels[0].1[..4], 2.5f32.to_le_bytes());
+	}
+
+	#[test]
+	fn layer_labels() {
+		let labels = calc_layer_labels([Some("dirt"), None, Some("stone")]).unwrap();
+		assert_eq!(labels.get("dirt"), Some(&0));
+		assert_eq!(labels.get("stone"), Some(&2));
+		assert_eq!(labels.len(), 2);
+
+		assert!(calc_layer_labels([Some("dirt"), Some("dirt")]).is_err());
+	}
+
+	#[rstest]
+	#[case(TextureLoadFormat::R8,          vec![51])]
+	#[case(TextureLoadFormat::Rg8,         vec![51, 128])]
+	#[case(TextureLoadFormat::Rgba8,       vec![51, 128, 255, 204])]
+	#[case(TextureLoadFormat::Rgba8Srgb,   vec![51, 128, 255, 204])]
+	#[case(TextureLoadFormat::Rgba16Float, vec![0x66, 0x32, 0x04, 0x38, 0x00, 0x3c, 0x66, 0x3a])]
+	fn convert_formats(#[case] format: TextureLoadFormat, #[case] expected: Vec<u8>) {
+		let image = image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(3, 2, Rgba([51, 128, 255, 204])));
+		let bytes = convert_image(&image, format);
+		assert_eq!(bytes.len(), 6 * format.bytes_per_pixel() as usize);
+		assert_eq!(&bytes[..expected.len()], &expected[..]);
+	}
+
+	#[test]
+	fn convert_fast_path() {
+		let image = image::RgbImage::from_fn(5, 3, |x, y| image::Rgb([x as u8 * 50, y as u8 * 90, 7]));
+		let image = image::DynamicImage::ImageRgb8(image);
+		let rgba = image.to_rgba8().into_raw();
+		assert_eq!(convert_image(&image, TextureLoadFormat::Rgba8Srgb), rgba);
+		assert_eq!(convert_image(&image, TextureLoadFormat::Rgba8), rgba);
+
+		// Same as the general path
+		assert_eq!(convert_pixels(&srgb_to_linear_image(&image.to_rgba8()), TextureLoadFormat::Rgba8Srgb), rgba);
+		assert_eq!(convert_pixels(&image.to_rgba32f(), TextureLoadFormat::Rgba8), rgba);
+	}
+
+	#[test]
+	fn convert_hdr() {
+		let image = image::DynamicImage::ImageRgba32F(Rgba32FImage::from_pixel(1, 1, Rgba([4.5, 0.25, 0., 1.])));
+		let bytes = convert_image(&image, TextureLoadFormat::Rgba32Float);
+		assert_eq!(bytes[..4], 4.5f32.to_le_bytes());
+		assert_eq!(bytes[4..8], 0.25f32.to_le_bytes());
+
+		// Values above 1 survive in half floats, but not in 8 bits
+		let bytes = convert_image(&image, TextureLoadFormat::Rgba16Float);
+		assert_eq!(bytes[..2], 0x4480u16.to_le_bytes());
+		assert_eq!(convert_image(&image, TextureLoadFormat::Rgba8)[0], 255);
+	}
+
+	#[test]
+	fn unfilterable_format() {
+		let options = TextureLoadOptions::from(FilterMode::Linear).with_format(TextureLoadFormat::Rgba32Float);
+		assert_eq!(
+			(options.mag_filter, options.min_filter, options.mipmap_filter),
+			(FilterMode::Nearest, FilterMode::Nearest, FilterMode::Nearest)
+		);
+
+		let options = TextureLoadOptions::default().with_format(TextureLoadFormat::Rgba16Float);
+		assert_eq!(options.min_filter, FilterMode::Linear);
+
+		// Opting back in, with the feature enabled
+		let options = options.with_format(TextureLoadFormat::Rgba32Float).with_filter(FilterMode::Linear);
+		assert_eq!(options.min_filter, FilterMode::Linear);
+	}
+
+	#[test]
+	fn convert_levels() {
+		let image = image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 4, Rgba([10, 20, 30, 40])));
+		let options = TextureLoadOptions::from(FilterMode::Nearest)
+			.with_format(TextureLoadFormat::Rg8)
+			.with_mipmaps(MipFilter::Box);
+		assert_eq!(options.min_filter, FilterMode::Nearest);
+
+		let levels = convert_image_levels(&image, &options);
+		assert_eq!(levels.len(), 4);
+		assert_eq!(levels[2], (Extent2::new(2, 1), vec![10, 20, 10, 20]));
+	}
+
+	#[rstest]
+	#[case(0.,             0x0000)]
+	#[case(-0.,            0x8000)]
+	#[case(1.,             0x3c00)]
+	#[case(-2.,            0xc000)]
+	#[case(0.1,            0x2e66)]
+	#[case(65504.,         0x7bff)]
+	#[case(1e6,            0x7c00)]
+	#[case(f32::NAN,       0x7e00)]
+	#[case(6.103_515_6e-5, 0x0400)]
+	#[case(5.960_464_5e-8, 0x0001)]
+	#[case(1e-9,           0x0000)]
+	fn half_float(#[case] value: f32, #[case] expected: u16) {
+		assert_eq!(f32_to_f16(value), expected);
+	}
+
+	#[test]
+	fn srgb_roundtrip() {
+		for value in 0..=255 {
+			assert_eq!(linear_to_srgb(srgb_to_linear(value)), value);
+		}
+	}
+}
@@ -0,0 +1,406 @@
+#![allow(dead_code)]
+
+use std::ops::Mul;
+
+use vek::{Lerp, Mat4, Quaternion, Vec3};
+
+#[cfg(feature = "camera_3d")]
+use crate::{Direction, Position};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// The placement of an object relative to its parent (or to the world if it
+/// has none): scaled first, then rotated, then translated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+	pub translation: Vec3<f32>,
+	pub rotation: Quaternion<f32>,
+	pub scale: Vec3<f32>,
+}
+
+/// The placement of an object in the world, the product of the [`Transform`]s
+/// of all its ancestors.
+///
+/// Stored as a matrix since combining rotations with non-uniform scales can
+/// shear, which a [`Transform`] can't represent.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlobalTransform(pub Mat4<f32>);
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl Transform {
+	pub const IDENTITY: Self = Self {
+		translation: Vec3::new(0., 0., 0.),
+		rotation: Quaternion {
+			x: 0.,
+			y: 0.,
+			z: 0.,
+			w: 1.,
+		},
+		scale: Vec3::new(1., 1., 1.),
+	};
+
+	pub fn from_translation(translation: Vec3<f32>) -> Self {
+		Self::IDENTITY.with_translation(translation)
+	}
+
+	pub fn from_rotation(rotation: Quaternion<f32>) -> Self {
+		Self::IDENTITY.with_rotation(rotation)
+	}
+
+	pub fn from_scale(scale: Vec3<f32>) -> Self {
+		Self::IDENTITY.with_scale(scale)
+	}
+
+	pub fn with_translation(mut self, translation: Vec3<f32>) -> Self {
+		self.translation = translation;
+		self
+	}
+
+	pub fn with_rotation(mut self, rotation: Quaternion<f32>) -> Self {
+		self.rotation = rotation;
+		self
+	}
+
+	pub fn with_scale(mut self, scale: Vec3<f32>) -> Self {
+		self.scale = scale;
+		self
+	}
+
+	pub fn with_look_at(mut self, target: Vec3<f32>, up: Vec3<f32>) -> Self {
+		self.look_at(target, up);
+		self
+	}
+
+	/// Rotates the transform so that its +Z axis points at `target`, and its +Y
+	/// axis is as close as possible to `up`.
+	///
+	/// The rotation is left unchanged if the target is on the translation, or
+	/// straight along `up`.
+	pub fn look_at(&mut self, target: Vec3<f32>, up: Vec3<f32>) {
+		let forward = target - self.translation;
+		let right = up.cross(forward);
+		if forward.magnitude_squared() <= f32::EPSILON || right.magnitude_squared() <= f32::EPSILON {
+			return;
+		}
+
+		let (forward, right) = (forward.normalized(), right.normalized());
+		self.rotation = calc_basis_rotation(right, forward.cross(right), forward);
+	}
+
+	/// The local +X axis.
+	pub fn right(&self) -> Vec3<f32> {
+		self.rotation * Vec3::unit_x()
+	}
+
+	/// The local +Y axis.
+	pub fn up(&self) -> Vec3<f32> {
+		self.rotation * Vec3::unit_y()
+	}
+
+	/// The local +Z axis.
+	pub fn forward(&self) -> Vec3<f32> {
+		self.rotation * Vec3::unit_z()
+	}
+
+	pub fn to_matrix(&self) -> Mat4<f32> {
+		Mat4::<f32>::translation_3d(self.translation) * Mat4::from(self.rotation) * Mat4::scaling_3d(self.scale)
+	}
+
+	pub fn transform_point(&self, point: Vec3<f32>) -> Vec3<f32> {
+		self.translation + self.transform_vector(point)
+	}
+
+	/// Transforms a direction or an offset, ignoring the translation.
+	pub fn transform_vector(&self, vector: Vec3<f32>) -> Vec3<f32> {
+		self.rotation * (vector * self.scale)
+	}
+
+	/// The transform applying `child` then `self`, placing a child in the space
+	/// of its parent.
+	///
+	/// Only exact if `self` has a uniform scale, or `child` no rotation: use
+	/// [`GlobalTransform`] otherwise.
+	pub fn mul_transform(&self, child: Transform) -> Transform {
+		Transform {
+			translation: self.transform_point(child.translation),
+			rotation: self.rotation * child.rotation,
+			scale: self.scale * child.scale,
+		}
+	}
+
+	/// The transform undoing this one.
+	///
+	/// Only exact with a uniform scale, or no rotation: use the inverse of
+	/// [`to_matrix`](Self::to_matrix) otherwise. The scale must not be zero.
+	pub fn inverse(&self) -> Transform {
+		let rotation = self.rotation.conjugate();
+		let scale = self.scale.recip();
+		Transform {
+			translation: rotation * -self.translation * scale,
+			rotation,
+			scale,
+		}
+	}
+
+	/// Interpolates the translation and scale linearly and the rotation along
+	/// the shortest arc. The factor `t` isn't clamped.
+	pub fn lerp(start: Self, end: Self, t: f32) -> Self {
+		Self {
+			translation: Lerp::lerp_unclamped(start.translation, end.translation, t),
+			rotation: Quaternion::slerp_unclamped(start.rotation, end.rotation, t),
+			scale: Lerp::lerp_unclamped(start.scale, end.scale, t),
+		}
+	}
+}
+
+#[cfg(feature = "camera_3d")]
+impl Transform {
+	/// The transform of a camera, with an unit scale.
+	pub fn from_position_direction(Position(position): Position, direction: Direction) -> Self {
+		Self::from_translation(position).with_rotation(direction.to_quaternion())
+	}
+
+	pub fn position(&self) -> Position {
+		Position(self.translation)
+	}
+
+	/// The direction the +Z axis points to, discarding any roll.
+	pub fn direction(&self) -> Direction {
+		Direction::from_quaternion(self.rotation)
+	}
+}
+
+impl Default for Transform {
+	fn default() -> Self {
+		Self::IDENTITY
+	}
+}
+
+impl Mul for Transform {
+	type Output = Transform;
+
+	fn mul(self, child: Transform) -> Transform {
+		self.mul_transform(child)
+	}
+}
+
+impl From<Transform> for Mat4<f32> {
+	fn from(transform: Transform) -> Self {
+		transform.to_matrix()
+	}
+}
+
+impl GlobalTransform {
+	/// The global transform of a child with a `local` transform.
+	pub fn mul_transform(&self, local: Transform) -> GlobalTransform {
+		GlobalTransform(self.0 * local.to_matrix())
+	}
+
+	pub fn translation(&self) -> Vec3<f32> {
+		self.0.cols.w.xyz()
+	}
+
+	pub fn transform_point(&self, point: Vec3<f32>) -> Vec3<f32> {
+		self.0.mul_point(point)
+	}
+
+	pub fn transform_vector(&self, vector: Vec3<f32>) -> Vec3<f32> {
+		self.0.mul_direction(vector)
+	}
+
+	pub fn to_matrix(&self) -> Mat4<f32> {
+		self.0
+	}
+}
+
+impl Default for GlobalTransform {
+	fn default() -> Self {
+		Self(Mat4::identity())
+	}
+}
+
+impl From<Transform> for GlobalTransform {
+	fn from(transform: Transform) -> Self {
+		Self(transform.to_matrix())
+	}
+}
+
+/// The rotation turning the X, Y and Z axes into an orthonormal basis.
+fn calc_basis_rotation(right: Vec3<f32>, up: Vec3<f32>, forward: Vec3<f32>) -> Quaternion<f32> {
+	// Converts the rotation matrix with the basis as columns, dividing by the
+	// largest component to stay precise
+	let trace = right.x + up.y + forward.z;
+	let (x, y, z, w) = if trace > 0. {
+		let s = (trace + 1.).sqrt() * 2.;
+		(
+			(up.z - forward.y) / s,
+			(forward.x - right.z) / s,
+			(right.y - up.x) / s,
+			s / 4.,
+		)
+	} else if right.x > up.y && right.x > forward.z {
+		let s = (1. + right.x - up.y - forward.z).sqrt() * 2.;
+		(
+			s / 4.,
+			(up.x + right.y) / s,
+			(forward.x + right.z) / s,
+			(up.z - forward.y) / s,
+		)
+	} else if up.y > forward.z {
+		let s = (1. + up.y - right.x - forward.z).sqrt() * 2.;
+		(
+			(up.x + right.y) / s,
+			s / 4.,
+			(forward.y + up.z) / s,
+			(forward.x - right.z) / s,
+		)
+	} else {
+		let s = (1. + forward.z - right.x - up.y).sqrt() * 2.;
+		(
+			(forward.x + right.z) / s,
+			(forward.y + up.z) / s,
+			s / 4.,
+			(right.y - up.x) / s,
+		)
+	};
+
+	Quaternion::from_xyzw(x, y, z, w)
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(feature = "bevy")]
+mod bevy {
+	use bevy_ecs::component::{Component, TableStorage};
+
+	use super::{GlobalTransform, Transform};
+
+	#[rustfmt::skip] impl Component for Transform       {type Storage = TableStorage;}
+	#[rustfmt::skip] impl Component for GlobalTransform {type Storage = TableStorage;}
+}
+
+#[cfg(feature = "bevy")]
+pub use bevy::*;
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use std::f32::consts::FRAC_PI_2;
+
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+
+	fn transform() -> Transform {
+		Transform::from_translation(Vec3::new(1., 2., 3.))
+			.with_rotation(Quaternion::rotation_y(0.7) * Quaternion::rotation_x(-0.3))
+			.with_scale(Vec3::broadcast(2.))
+	}
+
+	#[test]
+	fn matrix_matches_transform() {
+		let transform = transform().with_scale(Vec3::new(2., 0.5, 3.));
+		let point = Vec3::new(-4., 5., 0.5);
+		assert_relative_eq!(transform.to_matrix().mul_point(point), transform.transform_point(point), epsilon = 1e-5);
+		assert_relative_eq!(
+			transform.to_matrix().mul_direction(point),
+			transform.transform_vector(point),
+			epsilon = 1e-5
+		);
+	}
+
+	#[test]
+	fn compose() {
+		let parent = transform();
+		let child = Transform::from_translation(Vec3::unit_z()).with_rotation(Quaternion::rotation_z(1.));
+		let point = Vec3::new(-4., 5., 0.5);
+
+		let composed = parent * child;
+		assert_relative_eq!(composed.to_matrix(), parent.to_matrix() * child.to_matrix(), epsilon = 1e-5);
+		assert_relative_eq!(
+			composed.transform_point(point),
+			parent.transform_point(child.transform_point(point)),
+			epsilon = 1e-5
+		);
+		assert_eq!(Transform::IDENTITY * parent, parent);
+	}
+
+	#[test]
+	fn inverse() {
+		let transform = transform();
+		assert_relative_eq!((transform * transform.inverse()).to_matrix(), Mat4::identity(), epsilon = 1e-5);
+		assert_relative_eq!(transform.inverse().to_matrix(), transform.to_matrix().inverted(), epsilon = 1e-5);
+
+		// Without rotation, non-uniform scales are fine too
+		let transform = Transform::from_translation(Vec3::new(1., 2., 3.)).with_scale(Vec3::new(2., 4., 0.5));
+		assert_relative_eq!(transform.inverse().to_matrix(), transform.to_matrix().inverted(), epsilon = 1e-5);
+	}
+
+	#[rstest]
+	#[case(Vec3::new(1., 2., 10.))]
+	#[case(Vec3::new(-5., 2., 3.))]
+	#[case(Vec3::new(1., 2., -4.))]
+	#[case(Vec3::new(4., 7., 0.))]
+	#[case(Vec3::new(1.1, -8., 3.))]
+	fn look_at(#[case] target: Vec3<f32>) {
+		let transform = Transform::from_translation(Vec3::new(1., 2., 3.)).with_look_at(target, Vec3::unit_y());
+		assert_relative_eq!(transform.forward(), (target - transform.translation).normalized(), epsilon = 1e-5);
+		assert_relative_eq!(transform.right().y, 0., epsilon = 1e-5);
+		assert!(transform.up().y > 0.);
+		assert_relative_eq!(transform.rotation.magnitude(), 1., epsilon = 1e-5);
+	}
+
+	#[test]
+	fn look_at_degenerate() {
+		let mut transform = transform();
+		transform.look_at(transform.translation + Vec3::unit_y(), Vec3::unit_y());
+		assert_eq!(transform.rotation, self::transform().rotation);
+	}
+
+	#[test]
+	fn lerp() {
+		let start = Transform::IDENTITY;
+		let end = Transform::from_translation(Vec3::new(2., 0., 0.))
+			.with_rotation(Quaternion::rotation_y(FRAC_PI_2))
+			.with_scale(Vec3::broadcast(3.));
+
+		let half = Transform::lerp(start, end, 0.5);
+		assert_relative_eq!(half.translation, Vec3::new(1., 0., 0.));
+		assert_relative_eq!(half.rotation, Quaternion::rotation_y(FRAC_PI_2 / 2.), epsilon = 1e-6);
+		assert_relative_eq!(half.scale, Vec3::broadcast(2.));
+		assert_eq!(Transform::lerp(start, end, 0.), start);
+	}
+
+	#[test]
+	fn global_transform() {
+		// A non-uniform scale with a rotated child shears
+		let parent = transform().with_scale(Vec3::new(1., 3., 1.));
+		let child = Transform::from_rotation(Quaternion::rotation_z(1.));
+		let global = GlobalTransform::from(parent).mul_transform(child);
+		let point = Vec3::new(-4., 5., 0.5);
+
+		let expected = parent.transform_point(child.transform_point(point));
+		assert_relative_eq!(global.transform_point(point), expected, epsilon = 1e-5);
+		assert_eq!(global.translation(), parent.translation);
+		assert_eq!(GlobalTransform::default().to_matrix(), Mat4::identity());
+	}
+}
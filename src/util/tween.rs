@@ -0,0 +1,624 @@
+#![allow(dead_code)]
+
+use std::{
+	f32::consts::{PI, TAU},
+	ops::AddAssign,
+	time::Duration,
+};
+
+use vek::{Lerp, Vec2, Vec3, Vec4};
+
+#[cfg(feature = "camera_3d")]
+use crate::Direction;
+#[cfg(feature = "transform")]
+use crate::Transform;
+use crate::{Angle, AngleTurnType, AngleType};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// The standard Robert Penner easing functions.
+/// All of them map `0.` to `0.` and `1.` to `1.`, but `Back` and `Elastic`
+/// overshoot the `0..1` range in between.
+#[derive(Copy, Clone, Debug, Default)]
+pub enum Easing {
+	#[default]
+	Linear,
+	QuadIn,
+	QuadOut,
+	QuadInOut,
+	CubicIn,
+	CubicOut,
+	CubicInOut,
+	QuartIn,
+	QuartOut,
+	QuartInOut,
+	QuintIn,
+	QuintOut,
+	QuintInOut,
+	SineIn,
+	SineOut,
+	SineInOut,
+	ExpoIn,
+	ExpoOut,
+	ExpoInOut,
+	CircIn,
+	CircOut,
+	CircInOut,
+	BackIn,
+	BackOut,
+	BackInOut,
+	ElasticIn,
+	ElasticOut,
+	ElasticInOut,
+	BounceIn,
+	BounceOut,
+	BounceInOut,
+	Custom(fn(f32) -> f32),
+}
+
+/// How many times a [`Tween`] plays before it is finished.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Loops {
+	#[default]
+	Once,
+	Count(u32),
+	Infinite,
+}
+
+/// A value that can be interpolated by a [`Tween`].
+/// The factor `t` is usually in `0..1`, but may overshoot it with some easings.
+pub trait Tweenable: Copy {
+	fn tween(start: Self, end: Self, t: f32) -> Self;
+}
+
+/// Interpolates from `start` to `end` over `duration`, shaped by `easing`.
+#[derive(Copy, Clone, Debug)]
+pub struct Tween<T> {
+	pub start: T,
+	pub end: T,
+	pub duration: Duration,
+	pub easing: Easing,
+	pub delay: Duration,
+	pub loops: Loops,
+	pub yoyo: bool,
+	elapsed: Duration,
+}
+
+/// A chain of [`Tween`]s played one after the other.
+#[derive(Clone, Debug)]
+pub struct TweenSequence<T> {
+	tweens: Vec<Tween<T>>,
+	elapsed: Duration,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+const BACK_C1: f32 = 1.70158;
+const BACK_C2: f32 = BACK_C1 * 1.525;
+const BACK_C3: f32 = BACK_C1 + 1.;
+const ELASTIC_C4: f32 = TAU / 3.;
+const ELASTIC_C5: f32 = TAU / 4.5;
+
+impl Easing {
+	/// Applies the easing to a progress `t` in `0..1`.
+	pub fn apply(self, t: f32) -> f32 {
+		let t = t.clamp(0., 1.);
+
+		match self {
+			Easing::Linear => t,
+
+			Easing::QuadIn => t * t,
+			Easing::QuadOut => 1. - (1. - t).powi(2),
+			Easing::QuadInOut => in_out(t, |t| t * t),
+
+			Easing::CubicIn => t.powi(3),
+			Easing::CubicOut => 1. - (1. - t).powi(3),
+			Easing::CubicInOut => in_out(t, |t| t.powi(3)),
+
+			Easing::QuartIn => t.powi(4),
+			Easing::QuartOut => 1. - (1. - t).powi(4),
+			Easing::QuartInOut => in_out(t, |t| t.powi(4)),
+
+			Easing::QuintIn => t.powi(5),
+			Easing::QuintOut => 1. - (1. - t).powi(5),
+			Easing::QuintInOut => in_out(t, |t| t.powi(5)),
+
+			Easing::SineIn => 1. - (t * PI / 2.).cos(),
+			Easing::SineOut => (t * PI / 2.).sin(),
+			Easing::SineInOut => -((PI * t).cos() - 1.) / 2.,
+
+			Easing::ExpoIn if t == 0. => 0.,
+			Easing::ExpoIn => 2f32.powf(10. * t - 10.),
+			Easing::ExpoOut if t == 1. => 1.,
+			Easing::ExpoOut => 1. - 2f32.powf(-10. * t),
+			Easing::ExpoInOut if t == 0. || t == 1. => t,
+			Easing::ExpoInOut => in_out(t, |t| 2f32.powf(10. * t - 10.)),
+
+			Easing::CircIn => 1. - (1. - t * t).sqrt(),
+			Easing::CircOut => (1. - (t - 1.).powi(2)).sqrt(),
+			Easing::CircInOut => in_out(t, |t| 1. - (1. - t * t).sqrt()),
+
+			Easing::BackIn => BACK_C3 * t.powi(3) - BACK_C1 * t * t,
+			Easing::BackOut => 1. + BACK_C3 * (t - 1.).powi(3) + BACK_C1 * (t - 1.).powi(2),
+			Easing::BackInOut => in_out(t, |t| t * t * ((BACK_C2 + 1.) * t - BACK_C2)),
+
+			Easing::ElasticIn if t == 0. || t == 1. => t,
+			Easing::ElasticIn => -(2f32.powf(10. * t - 10.)) * ((t * 10. - 10.75) * ELASTIC_C4).sin(),
+			Easing::ElasticOut if t == 0. || t == 1. => t,
+			Easing::ElasticOut => 2f32.powf(-10. * t) * ((t * 10. - 0.75) * ELASTIC_C4).sin() + 1.,
+			Easing::ElasticInOut if t == 0. || t == 1. => t,
+			Easing::ElasticInOut if t < 0.5 => -(2f32.powf(20. * t - 10.) * ((20. * t - 11.125) * ELASTIC_C5).sin()) / 2.,
+			Easing::ElasticInOut => 2f32.powf(-20. * t + 10.) * ((20. * t - 11.125) * ELASTIC_C5).sin() / 2. + 1.,
+
+			Easing::BounceIn => 1. - bounce_out(1. - t),
+			Easing::BounceOut => bounce_out(t),
+			Easing::BounceInOut => in_out(t, |t| 1. - bounce_out(1. - t)),
+
+			Easing::Custom(f) => f(t),
+		}
+	}
+}
+
+/// Builds the symmetric in-out variant of an "in" easing.
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+	if t < 0.5 {
+		ease_in(t * 2.) / 2.
+	} else {
+		1. - ease_in((1. - t) * 2.) / 2.
+	}
+}
+
+fn bounce_out(t: f32) -> f32 {
+	const N1: f32 = 7.5625;
+	const D1: f32 = 2.75;
+
+	if t < 1. / D1 {
+		N1 * t * t
+	} else if t < 2. / D1 {
+		let t = t - 1.5 / D1;
+		N1 * t * t + 0.75
+	} else if t < 2.5 / D1 {
+		let t = t - 2.25 / D1;
+		N1 * t * t + 0.9375
+	} else {
+		let t = t - 2.625 / D1;
+		N1 * t * t + 0.984375
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl<T: Tweenable> Tween<T> {
+	pub fn new(start: T, end: T, duration: Duration, easing: Easing) -> Self {
+		Self {
+			start,
+			end,
+			duration,
+			easing,
+			delay: Duration::ZERO,
+			loops: Loops::Once,
+			yoyo: false,
+			elapsed: Duration::ZERO,
+		}
+	}
+
+	pub fn with_delay(mut self, delay: Duration) -> Self {
+		self.delay = delay;
+		self
+	}
+
+	pub fn with_loops(mut self, loops: Loops) -> Self {
+		self.loops = loops;
+		self
+	}
+
+	/// Every second loop is played backwards, from `end` to `start`.
+	pub fn with_yoyo(mut self, yoyo: bool) -> Self {
+		self.yoyo = yoyo;
+		self
+	}
+
+	/// The time from the start of the delay to the end of the last loop, or
+	/// [`None`] if the tween loops infinitely. A zero duration ends right after
+	/// the delay, even when looping infinitely. Saturates to [`Duration::MAX`].
+	pub fn total_duration(&self) -> Option<Duration> {
+		let loops = match self.loops {
+			_ if self.duration.is_zero() => Duration::ZERO,
+			Loops::Once => self.duration,
+			Loops::Count(count) => self.duration.checked_mul(count).unwrap_or(Duration::MAX),
+			Loops::Infinite => return None,
+		};
+		Some(self.delay.saturating_add(loops))
+	}
+
+	pub fn elapsed(&self) -> Duration {
+		self.elapsed
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.total_duration().is_some_and(|total| self.elapsed >= total)
+	}
+
+	pub fn reset(&mut self) {
+		self.elapsed = Duration::ZERO;
+	}
+
+	/// Advances the tween by `delta` and returns the new value.
+	pub fn tick(&mut self, delta: Duration) -> T {
+		self.elapsed += delta;
+		self.value()
+	}
+
+	/// The value at the current elapsed time.
+	pub fn value(&self) -> T {
+		self.value_at(self.elapsed)
+	}
+
+	/// The value at an arbitrary time since the start of the tween, delay
+	/// included.
+	pub fn value_at(&self, elapsed: Duration) -> T {
+		T::tween(self.start, self.end, self.easing.apply(self.progress_at(elapsed)))
+	}
+
+	/// The un-eased progress of the current loop in `0..1`, already reversed
+	/// when yoyo-ing.
+	pub fn progress_at(&self, elapsed: Duration) -> f32 {
+		let Some(local) = elapsed.checked_sub(self.delay) else {
+			return 0.;
+		};
+
+		let loop_count = match self.loops {
+			Loops::Once => Some(1),
+			Loops::Count(count) => Some(count),
+			Loops::Infinite => None,
+		};
+
+		if loop_count == Some(0) {
+			return 0.;
+		}
+
+		// A zero duration ends immediately, there is no loop to land in when
+		// looping forever
+		let cycles = if self.duration.is_zero() {
+			if loop_count.is_none() {
+				return 1.;
+			}
+			f64::INFINITY
+		} else {
+			local.as_secs_f64() / self.duration.as_secs_f64()
+		};
+
+		let (cycle, progress) = match loop_count {
+			Some(count) if cycles >= count as f64 => (count - 1, 1.),
+			_ => (cycles.floor() as u32, cycles.fract() as f32),
+		};
+
+		if self.yoyo && cycle % 2 == 1 {
+			1. - progress
+		} else {
+			progress
+		}
+	}
+}
+
+impl<T: Tweenable> AddAssign<Duration> for Tween<T> {
+	fn add_assign(&mut self, delta: Duration) {
+		self.elapsed += delta;
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl<T: Tweenable> TweenSequence<T> {
+	pub fn new(first: Tween<T>) -> Self {
+		Self {
+			tweens: vec![first],
+			elapsed: Duration::ZERO,
+		}
+	}
+
+	/// Appends a tween that starts once all the previous ones are finished.
+	pub fn then(mut self, tween: Tween<T>) -> Self {
+		self.tweens.push(tween);
+		self
+	}
+
+	pub fn tweens(&self) -> &[Tween<T>] {
+		&self.tweens
+	}
+
+	/// The sum of all the tweens' durations, or [`None`] if any of them loops
+	/// infinitely. Saturates to [`Duration::MAX`].
+	pub fn total_duration(&self) -> Option<Duration> {
+		self.tweens
+			.iter()
+			.try_fold(Duration::ZERO, |total, tween| Some(total.saturating_add(tween.total_duration()?)))
+	}
+
+	pub fn elapsed(&self) -> Duration {
+		self.elapsed
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.total_duration().is_some_and(|total| self.elapsed >= total)
+	}
+
+	pub fn reset(&mut self) {
+		self.elapsed = Duration::ZERO;
+	}
+
+	/// Advances the sequence by `delta` and returns the new value.
+	pub fn tick(&mut self, delta: Duration) -> T {
+		self.elapsed += delta;
+		self.value()
+	}
+
+	pub fn value(&self) -> T {
+		self.value_at(self.elapsed)
+	}
+
+	pub fn value_at(&self, mut elapsed: Duration) -> T {
+		let (last, rest) = self.tweens.split_last().expect("A sequence always has at least one tween");
+
+		for tween in rest {
+			match tween.total_duration() {
+				Some(total) if elapsed >= total => elapsed -= total,
+				_ => return tween.value_at(elapsed),
+			}
+		}
+
+		last.value_at(elapsed)
+	}
+}
+
+impl<T: Tweenable> AddAssign<Duration> for TweenSequence<T> {
+	fn add_assign(&mut self, delta: Duration) {
+		self.elapsed += delta;
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[rustfmt::skip] impl Tweenable for f32      {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t)}}
+#[rustfmt::skip] impl Tweenable for f64      {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t as f64)}}
+#[rustfmt::skip] impl Tweenable for Vec2<f32> {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t)}}
+#[rustfmt::skip] impl Tweenable for Vec3<f32> {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t)}}
+#[rustfmt::skip] impl Tweenable for Vec4<f32> {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t)}}
+#[rustfmt::skip] impl Tweenable for Vec2<f64> {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t as f64)}}
+#[rustfmt::skip] impl Tweenable for Vec3<f64> {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t as f64)}}
+#[rustfmt::skip] impl Tweenable for Vec4<f64> {fn tween(start: Self, end: Self, t: f32) -> Self {Lerp::lerp_unclamped(start, end, t as f64)}}
+
+/// Angles are interpolated along the shortest path, so tweening from 350° to
+/// 10° goes through 0° rather than all the way around.
+impl<U: AngleType + Copy> Tweenable for Angle<f32, U>
+where
+	Self: AngleTurnType,
+{
+	fn tween(start: Self, end: Self, t: f32) -> Self {
+		let (full, half) = (Self::full_turn(), Self::half_turn());
+		let delta = (((end - start) % full) + full + half) % full - half;
+		start + delta * t
+	}
+}
+
+impl<U: AngleType + Copy> Tweenable for Angle<f64, U>
+where
+	Self: AngleTurnType,
+{
+	fn tween(start: Self, end: Self, t: f32) -> Self {
+		let (full, half) = (Self::full_turn(), Self::half_turn());
+		let delta = (((end - start) % full) + full + half) % full - half;
+		start + delta * t as f64
+	}
+}
+
+/// The yaw takes the shortest path, the pitch is interpolated linearly since
+/// it never wraps around.
+#[cfg(feature = "camera_3d")]
+impl Tweenable for Direction {
+	fn tween(start: Self, end: Self, t: f32) -> Self {
+		Self {
+			yaw: Angle::tween(start.yaw, end.yaw, t),
+			pitch: start.pitch + (end.pitch - start.pitch) * t,
+		}
+	}
+}
+
+#[cfg(feature = "transform")]
+impl Tweenable for Transform {
+	fn tween(start: Self, end: Self, t: f32) -> Self {
+		Transform::lerp(start, end, t)
+	}
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use approx::assert_relative_eq;
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{AngleDegreesType, Degrees};
+
+	#[rstest]
+	#[case(Easing::Linear)]
+	#[case(Easing::QuadIn)]
+	#[case(Easing::QuadOut)]
+	#[case(Easing::QuadInOut)]
+	#[case(Easing::CubicIn)]
+	#[case(Easing::CubicOut)]
+	#[case(Easing::CubicInOut)]
+	#[case(Easing::QuartIn)]
+	#[case(Easing::QuartOut)]
+	#[case(Easing::QuartInOut)]
+	#[case(Easing::QuintIn)]
+	#[case(Easing::QuintOut)]
+	#[case(Easing::QuintInOut)]
+	#[case(Easing::SineIn)]
+	#[case(Easing::SineOut)]
+	#[case(Easing::SineInOut)]
+	#[case(Easing::ExpoIn)]
+	#[case(Easing::ExpoOut)]
+	#[case(Easing::ExpoInOut)]
+	#[case(Easing::CircIn)]
+	#[case(Easing::CircOut)]
+	#[case(Easing::CircInOut)]
+	#[case(Easing::BackIn)]
+	#[case(Easing::BackOut)]
+	#[case(Easing::BackInOut)]
+	#[case(Easing::ElasticIn)]
+	#[case(Easing::ElasticOut)]
+	#[case(Easing::ElasticInOut)]
+	#[case(Easing::BounceIn)]
+	#[case(Easing::BounceOut)]
+	#[case(Easing::BounceInOut)]
+	fn easing_endpoints(#[case] easing: Easing) {
+		assert_relative_eq!(easing.apply(0.), 0., epsilon = 1e-3);
+		assert_relative_eq!(easing.apply(1.), 1., epsilon = 1e-3);
+	}
+
+	#[rstest]
+	#[case(Easing::QuadInOut)]
+	#[case(Easing::CubicInOut)]
+	#[case(Easing::SineInOut)]
+	#[case(Easing::CircInOut)]
+	#[case(Easing::BackInOut)]
+	#[case(Easing::BounceInOut)]
+	fn easing_in_out_is_symmetric(#[case] easing: Easing) {
+		assert_relative_eq!(easing.apply(0.5), 0.5, epsilon = 1e-5);
+		assert_relative_eq!(easing.apply(0.2), 1. - easing.apply(0.8), epsilon = 1e-5);
+	}
+
+	#[test]
+	fn easing_overshoot() {
+		assert!(Easing::BackIn.apply(0.2) < 0.);
+		assert!(Easing::BackOut.apply(0.8) > 1.);
+		assert!(Easing::ElasticOut.apply(0.1) > 1.);
+	}
+
+	#[test]
+	fn tween_scalar() {
+		let mut tween = Tween::new(0., 10., Duration::from_secs(2), Easing::Linear);
+		assert_relative_eq!(tween.value(), 0.);
+		assert_relative_eq!(tween.tick(Duration::from_millis(500)), 2.5);
+		assert_relative_eq!(tween.tick(Duration::from_millis(1500)), 10.);
+		assert!(tween.is_finished());
+		assert_relative_eq!(tween.tick(Duration::from_secs(5)), 10.);
+	}
+
+	#[test]
+	fn tween_delay() {
+		let tween = Tween::new(0., 1., Duration::from_secs(1), Easing::Linear).with_delay(Duration::from_secs(1));
+		assert_relative_eq!(tween.value_at(Duration::from_millis(500)), 0.);
+		assert_relative_eq!(tween.value_at(Duration::from_millis(1500)), 0.5);
+		assert_eq!(tween.total_duration(), Some(Duration::from_secs(2)));
+	}
+
+	#[test]
+	fn tween_yoyo_loops() {
+		let tween = Tween::new(0., 1., Duration::from_secs(1), Easing::Linear)
+			.with_loops(Loops::Count(3))
+			.with_yoyo(true);
+		assert_relative_eq!(tween.value_at(Duration::from_millis(250)), 0.25);
+		assert_relative_eq!(tween.value_at(Duration::from_millis(1250)), 0.75);
+		assert_relative_eq!(tween.value_at(Duration::from_millis(2250)), 0.25);
+		assert_relative_eq!(tween.value_at(Duration::from_secs(10)), 1.);
+
+		let tween = tween.with_loops(Loops::Infinite);
+		assert_eq!(tween.total_duration(), None);
+		assert_relative_eq!(tween.value_at(Duration::from_millis(101_250)), 0.75);
+	}
+
+	#[rstest]
+	#[case(Loops::Once)]
+	#[case(Loops::Count(3))]
+	#[case(Loops::Infinite)]
+	fn tween_zero_duration(#[case] loops: Loops) {
+		let tween = Tween::new(0., 1., Duration::ZERO, Easing::Linear).with_loops(loops);
+		assert_eq!(tween.progress_at(Duration::ZERO), 1.);
+		assert_relative_eq!(tween.value_at(Duration::from_secs(1)), 1.);
+		assert_eq!(tween.total_duration(), Some(Duration::ZERO));
+		assert!(tween.is_finished());
+	}
+
+	#[test]
+	fn tween_duration_overflow() {
+		let mut tween = Tween::new(0., 1., Duration::from_secs(u64::MAX / 2), Easing::Linear)
+			.with_loops(Loops::Count(u32::MAX))
+			.with_delay(Duration::from_secs(1));
+		assert_eq!(tween.total_duration(), Some(Duration::MAX));
+		assert!(!tween.is_finished());
+		assert_relative_eq!(tween.tick(Duration::from_secs(1)), 0.);
+
+		let sequence = TweenSequence::new(tween).then(Tween::new(0., 1., Duration::from_secs(1), Easing::Linear));
+		assert_eq!(sequence.total_duration(), Some(Duration::MAX));
+	}
+
+	#[test]
+	fn tween_sequence() {
+		let mut sequence = TweenSequence::new(Tween::new(0., 1., Duration::from_secs(1), Easing::Linear))
+			.then(Tween::new(1., 3., Duration::from_secs(2), Easing::Linear));
+		assert_eq!(sequence.total_duration(), Some(Duration::from_secs(3)));
+		assert_relative_eq!(sequence.tick(Duration::from_millis(500)), 0.5);
+		assert_relative_eq!(sequence.tick(Duration::from_secs(1)), 1.5);
+		assert_relative_eq!(sequence.tick(Duration::from_secs(5)), 3.);
+		assert!(sequence.is_finished());
+	}
+
+	#[test]
+	fn tween_vector() {
+		let tween = Tween::new(Vec3::zero(), Vec3::new(2., 4., 6.), Duration::from_secs(1), Easing::Linear);
+		assert_relative_eq!(tween.value_at(Duration::from_millis(500)), Vec3::new(1., 2., 3.));
+	}
+
+	#[rstest]
+	#[case(350., 10., 0.5, 360.)]
+	#[case(10., 350., 0.5, 0.)]
+	#[case(0., 90., 0.5, 45.)]
+	#[case(-170., 170., 0.5, -180.)]
+	fn tween_angle_shortest_path(#[case] start: f32, #[case] end: f32, #[case] t: f32, #[case] expected: f32) {
+		let value = Degrees::tween(Degrees::new(start), Degrees::new(end), t);
+		assert_relative_eq!(value.degrees(), expected, epsilon = 1e-4);
+	}
+
+	#[test]
+	#[cfg(feature = "camera_3d")]
+	fn tween_direction() {
+		let start = Direction {
+			yaw: Degrees::new(350.).into(),
+			pitch: Degrees::new(-10.).into(),
+		};
+		let end = Direction {
+			yaw: Degrees::new(10.).into(),
+			pitch: Degrees::new(30.).into(),
+		};
+		let value = Direction::tween(start, end, 0.5);
+		assert_relative_eq!(value.yaw.degrees(), 360., epsilon = 1e-3);
+		assert_relative_eq!(value.pitch.degrees(), 10., epsilon = 1e-3);
+	}
+}
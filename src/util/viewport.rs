@@ -0,0 +1,256 @@
+#![allow(dead_code)]
+
+use vek::{Extent2, Mat4, Vec2};
+
+use crate::{calc_projection_matrix, Projection, ScreenSize};
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+/// A rectangle of a surface to render to, in pixels from the top-left corner of
+/// the surface, like the arguments of wgpu's `RenderPass::set_viewport`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+	pub position: Vec2<u32>,
+	pub size: ScreenSize,
+	pub min_depth: f32,
+	pub max_depth: f32,
+}
+
+/// How the screen is divided between two players.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SplitDirection {
+	/// Side by side.
+	#[default]
+	Vertical,
+	/// One above the other.
+	Horizontal,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Corner {
+	TopLeft,
+	#[default]
+	TopRight,
+	BottomLeft,
+	BottomRight,
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+impl Viewport {
+	pub fn new(position: Vec2<u32>, size: ScreenSize) -> Self {
+		Self {
+			position,
+			size,
+			min_depth: 0.,
+			max_depth: 1.,
+		}
+	}
+
+	/// The viewport covering the whole surface.
+	pub fn full(surface: ScreenSize) -> Self {
+		Self::new(Vec2::zero(), surface)
+	}
+
+	pub fn with_depth(mut self, min_depth: f32, max_depth: f32) -> Self {
+		self.min_depth = min_depth;
+		self.max_depth = max_depth;
+		self
+	}
+
+	pub fn aspect_ratio(&self) -> f32 {
+		self.size.w as f32 / self.size.h as f32
+	}
+
+	/// The projection matrix for this viewport, with its own aspect ratio.
+	pub fn projection_matrix(&self, projection: impl Into<Projection>, jitter: Option<Vec2<f32>>) -> Mat4<f32> {
+		calc_projection_matrix(projection, self.size, jitter)
+	}
+
+	/// Whether a cursor position on the surface is inside the viewport.
+	pub fn contains(&self, cursor: Vec2<f32>) -> bool {
+		let local = self.to_local(cursor);
+		local.x >= 0. && local.y >= 0. && local.x < self.size.w as f32 && local.y < self.size.h as f32
+	}
+
+	/// Converts a cursor position on the surface to a position relative to the
+	/// viewport, which can be used with the viewport's size for picking.
+	pub fn to_local(&self, cursor: Vec2<f32>) -> Vec2<f32> {
+		cursor - self.position.as_::<f32>()
+	}
+
+	/// Converts a position relative to the viewport to a position on the
+	/// surface.
+	pub fn to_surface(&self, local: Vec2<f32>) -> Vec2<f32> {
+		local + self.position.as_::<f32>()
+	}
+}
+
+/// Divides the surface between 1 to 4 players. The viewports cover the whole
+/// surface without overlapping, in player order:
+/// - 2 players are split according to `direction`
+/// - 3 players have the first one on the top half, the others sharing the
+///   bottom half
+/// - 4 players are in a 2x2 grid, left to right then top to bottom
+pub fn calc_split_screen(surface: ScreenSize, players: usize, direction: SplitDirection) -> Vec<Viewport> {
+	assert!((1..=4).contains(&players), "split-screen supports 1 to 4 players, not {players}");
+
+	let Extent2 { w, h } = surface;
+	// The second halves get the extra pixel of odd sizes
+	let (left, right) = (w / 2, w - w / 2);
+	let (top, bottom) = (h / 2, h - h / 2);
+	let viewport = |x, y, w, h| Viewport::new(Vec2::new(x, y), ScreenSize::new(w, h));
+
+	match (players, direction) {
+		(1, _) => vec![Viewport::full(surface)],
+		(2, SplitDirection::Vertical) => vec![viewport(0, 0, left, h), viewport(left, 0, right, h)],
+		(2, SplitDirection::Horizontal) => vec![viewport(0, 0, w, top), viewport(0, top, w, bottom)],
+		(3, _) => vec![
+			viewport(0, 0, w, top),
+			viewport(0, top, left, bottom),
+			viewport(left, top, right, bottom),
+		],
+		_ => vec![
+			viewport(0, 0, left, top),
+			viewport(left, 0, right, top),
+			viewport(0, top, left, bottom),
+			viewport(left, top, right, bottom),
+		],
+	}
+}
+
+/// A full-surface viewport and a smaller one in a corner on top of it, like
+/// for a rear-view mirror or a minimap.
+///
+/// `scale` is the size of the inset relative to the surface, and `margin` its
+/// distance to the edges, in pixels.
+pub fn calc_picture_in_picture(surface: ScreenSize, scale: f32, corner: Corner, margin: u32) -> [Viewport; 2] {
+	let size = (surface.as_::<f32>() * scale).round().as_::<u32>();
+	let (left, top) = (margin, margin);
+	let right = surface.w.saturating_sub(size.w + margin);
+	let bottom = surface.h.saturating_sub(size.h + margin);
+
+	let position = match corner {
+		Corner::TopLeft => Vec2::new(left, top),
+		Corner::TopRight => Vec2::new(right, top),
+		Corner::BottomLeft => Vec2::new(left, bottom),
+		Corner::BottomRight => Vec2::new(right, bottom),
+	};
+
+	[Viewport::full(surface), Viewport::new(position, size)]
+}
+
+/// Finds the viewport under a cursor, and the cursor position relative to it.
+/// Later viewports are considered on top of earlier ones.
+pub fn find_viewport(viewports: &[Viewport], cursor: Vec2<f32>) -> Option<(usize, Vec2<f32>)> {
+	viewports
+		.iter()
+		.enumerate()
+		.rev()
+		.find(|(_, viewport)| viewport.contains(cursor))
+		.map(|(i, viewport)| (i, viewport.to_local(cursor)))
+}
+
+/*
+--------------------------------------------------------------------------------
+||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||||
+--------------------------------------------------------------------------------
+*/
+
+#[cfg(test)]
+mod tests {
+	use rstest::rstest;
+
+	use super::*;
+	use crate::{Frustum, Radians};
+
+	const SURFACE: ScreenSize = ScreenSize::new(1921, 1081);
+
+	#[rstest]
+	#[case(1, SplitDirection::Vertical)]
+	#[case(2, SplitDirection::Vertical)]
+	#[case(2, SplitDirection::Horizontal)]
+	#[case(3, SplitDirection::Vertical)]
+	#[case(4, SplitDirection::Vertical)]
+	fn split_screen_covers_surface(#[case] players: usize, #[case] direction: SplitDirection) {
+		let viewports = calc_split_screen(SURFACE, players, direction);
+		assert_eq!(viewports.len(), players);
+
+		// Every pixel belongs to exactly one viewport
+		for y in (0..SURFACE.h).step_by(20).chain([539, 540, 541, 1080]) {
+			for x in (0..SURFACE.w).step_by(20).chain([959, 960, 961, 1920]) {
+				let cursor = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+				let count = viewports.iter().filter(|viewport| viewport.contains(cursor)).count();
+				assert_eq!(count, 1, "{cursor:?}");
+			}
+		}
+	}
+
+	#[test]
+	fn split_screen_layouts() {
+		let vertical = calc_split_screen(SURFACE, 2, SplitDirection::Vertical);
+		assert_eq!(vertical[0].size, ScreenSize::new(960, 1081));
+		assert_eq!(vertical[1].position, Vec2::new(960, 0));
+		assert_eq!(vertical[1].size, ScreenSize::new(961, 1081));
+
+		let three = calc_split_screen(SURFACE, 3, SplitDirection::Vertical);
+		assert_eq!(three[0].size, ScreenSize::new(1921, 540));
+		assert_eq!(three[2].position, Vec2::new(960, 540));
+
+		let four = calc_split_screen(SURFACE, 4, SplitDirection::Vertical);
+		assert_eq!(four[1].position, Vec2::new(960, 0));
+		assert_eq!(four[2].position, Vec2::new(0, 540));
+	}
+
+	#[test]
+	#[should_panic]
+	fn split_screen_too_many_players() {
+		calc_split_screen(SURFACE, 5, SplitDirection::Vertical);
+	}
+
+	#[rstest]
+	#[case(Corner::TopLeft,     Vec2::new(10, 10))]
+	#[case(Corner::TopRight,    Vec2::new(390, 10))]
+	#[case(Corner::BottomLeft,  Vec2::new(10, 290))]
+	#[case(Corner::BottomRight, Vec2::new(390, 290))]
+	fn picture_in_picture(#[case] corner: Corner, #[case] expected: Vec2<u32>) {
+		let [main, inset] = calc_picture_in_picture(ScreenSize::new(800, 600), 0.5, corner, 10);
+		assert_eq!(main, Viewport::full(ScreenSize::new(800, 600)));
+		assert_eq!(inset.position, expected);
+		assert_eq!(inset.size, ScreenSize::new(400, 300));
+	}
+
+	#[test]
+	fn cursor_to_viewport() {
+		let viewports = calc_picture_in_picture(ScreenSize::new(800, 600), 0.25, Corner::TopRight, 0);
+		assert_eq!(find_viewport(&viewports, Vec2::new(700., 50.)), Some((1, Vec2::new(100., 50.))));
+		assert_eq!(find_viewport(&viewports, Vec2::new(100., 50.)), Some((0, Vec2::new(100., 50.))));
+		assert_eq!(find_viewport(&viewports, Vec2::new(900., 50.)), None);
+
+		let viewport = viewports[1];
+		assert_eq!(viewport.to_surface(viewport.to_local(Vec2::new(12., 34.))), Vec2::new(12., 34.));
+	}
+
+	#[test]
+	fn projection_aspect_ratio() {
+		let frustum = Frustum {
+			y_fov: Radians::new(1.),
+			z_near: 0.1,
+			z_far: 100.,
+		};
+		let viewport = calc_split_screen(ScreenSize::new(1600, 600), 2, SplitDirection::Vertical)[1];
+		assert_eq!(viewport.aspect_ratio(), 800. / 600.);
+		assert_eq!(
+			viewport.projection_matrix(frustum, None),
+			calc_projection_matrix(frustum, ScreenSize::new(800, 600), None)
+		);
+	}
+}